@@ -1,4 +1,11 @@
 use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Distance {
+    Hamming,
+    Edit,
+}
 
 pub fn part1(input: &[String]) -> u32 {
     let (total_dbl, total_tpl) = input
@@ -22,17 +29,99 @@ fn has_double_and_triple(word: &str) -> (bool, bool) {
     )
 }
 
-pub fn part2(input: &[String]) -> String {
+pub fn part2(input: &[String]) -> Result<String, Error> {
+    common_letters(input, Distance::Hamming)
+}
+
+pub fn common_letters(
+    input: &[String],
+    distance: Distance,
+) -> Result<String, Error> {
+    if distance == Distance::Hamming {
+        check_equal_length(input)?;
+    }
+
     for (idx1, str1) in input.iter().enumerate() {
-        let str1_len = str1.chars().count();
-        for str2 in input[idx1..].iter() {
-            let equal = equal_chars(str1, str2);
-            if equal.len() == str1_len - 1 {
-                return equal.into_iter().collect();
+        for str2 in input[idx1 + 1..].iter() {
+            let common = match distance {
+                Distance::Hamming => hamming_common(str1, str2),
+                Distance::Edit => edit_common(str1, str2),
+            };
+            if let Some(common) = common {
+                return Ok(common);
             }
         }
     }
-    "".to_string()
+    Ok("".to_string())
+}
+
+fn check_equal_length(input: &[String]) -> Result<(), Error> {
+    let expected_len = match input.first() {
+        Some(first) => first.chars().count(),
+        None => return Ok(()),
+    };
+
+    match input
+        .iter()
+        .enumerate()
+        .find(|(_, id)| id.chars().count() != expected_len)
+    {
+        Some((idx, id)) => Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "ID '{}' on line {} has length {}, expected {}",
+                id,
+                idx + 1,
+                id.chars().count(),
+                expected_len
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn hamming_common(str1: &str, str2: &str) -> Option<String> {
+    let str1_len = str1.chars().count();
+    if str1_len == 0 || str1_len != str2.chars().count() {
+        return None;
+    }
+
+    let equal = equal_chars(str1, str2);
+    if equal.len() == str1_len - 1 {
+        Some(equal.into_iter().collect())
+    } else {
+        None
+    }
+}
+
+fn edit_common(str1: &str, str2: &str) -> Option<String> {
+    let chars1: Vec<char> = str1.chars().collect();
+    let chars2: Vec<char> = str2.chars().collect();
+    let (shorter, longer) = if chars1.len() <= chars2.len() {
+        (chars1, chars2)
+    } else {
+        (chars2, chars1)
+    };
+
+    if shorter.len() == longer.len() {
+        return hamming_common(str1, str2);
+    }
+    if shorter.len() + 1 != longer.len() {
+        return None;
+    }
+
+    // A single insertion or deletion: both IDs must agree everywhere
+    // except for the one extra character in the longer ID
+    let skip = shorter
+        .iter()
+        .zip(longer.iter())
+        .position(|(c1, c2)| c1 != c2)
+        .unwrap_or(shorter.len());
+    if shorter[skip..] == longer[skip + 1..] {
+        Some(shorter.into_iter().collect())
+    } else {
+        None
+    }
 }
 
 fn equal_chars(str1: &str, str2: &str) -> Vec<char> {
@@ -1,30 +1,45 @@
 use clap::{crate_description, App, Arg};
-use day02::{part1, part2};
+use day02::{common_letters, part1, Distance};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::process::exit;
 
 fn main() {
-    let args = App::new(crate_description!())
-        .arg(
-            Arg::with_name("INPUT")
-                .help("Sets the input file to use")
-                .required(true)
-                .index(1),
-        )
-        .get_matches();
+    let args =
+        App::new(crate_description!())
+            .arg(
+                Arg::with_name("INPUT")
+                    .help("Sets the input file to use")
+                    .required(true)
+                    .index(1),
+            )
+            .arg(Arg::with_name("edit-distance").long("edit-distance").help(
+                "Also matches IDs that differ by an insertion or deletion",
+            ))
+            .get_matches();
 
     println!(crate_description!());
     let input = read_input(args.value_of("INPUT").unwrap());
+    let distance = if args.is_present("edit-distance") {
+        Distance::Edit
+    } else {
+        Distance::Hamming
+    };
     println!("Part 1: {}", part1(&input));
-    println!("Part 2: {}", part2(&input));
+    match common_letters(&input, distance) {
+        Ok(common) => println!("Part 2: {}", common),
+        Err(err) => {
+            println!("Invalid input: {}", err);
+            exit(4);
+        }
+    }
 }
 
 fn read_input(filename: &str) -> Vec<String> {
     let file = match File::open(filename) {
         Ok(file) => file,
         Err(err) => {
-            println!("Failed to open file '{}': {}", filename, err.to_string());
+            println!("Failed to open file '{}': {}", filename, err);
             exit(2);
         }
     };
@@ -32,11 +47,7 @@ fn read_input(filename: &str) -> Vec<String> {
     match BufReader::new(file).lines().collect() {
         Ok(input) => input,
         Err(err) => {
-            println!(
-                "Failed to parse input file '{}': {}",
-                filename,
-                err.to_string()
-            );
+            println!("Failed to parse input file '{}': {}", filename, err);
             exit(3);
         }
     }
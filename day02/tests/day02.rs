@@ -1,4 +1,4 @@
-use day02::{part1, part2};
+use day02::{common_letters, part1, part2, Distance};
 
 #[test]
 fn examples_part1() {
@@ -25,5 +25,33 @@ fn examples_part2() {
         "axcye".to_string(),
         "wvxyz".to_string(),
     ];
-    assert_eq!(part2(&input), "fgij");
+    assert_eq!(part2(&input).unwrap(), "fgij");
+}
+
+#[test]
+fn part2_unequal_lengths() {
+    let input = [
+        "abcde".to_string(),
+        "fghij".to_string(),
+        "klmn".to_string(),
+        "fguij".to_string(),
+    ];
+    let err = part2(&input).unwrap_err();
+    assert!(err.to_string().contains("line 3"));
+}
+
+#[test]
+fn part2_edit_distance() {
+    let input = [
+        "abcde".to_string(),
+        "fghij".to_string(),
+        "klmno".to_string(),
+        "fgij".to_string(),
+        "axcye".to_string(),
+    ];
+    assert_eq!(common_letters(&input, Distance::Edit).unwrap(), "fgij");
+    assert!(common_letters(&input, Distance::Hamming).is_err());
+
+    let input = ["abcd".to_string(), "abcde".to_string()];
+    assert_eq!(common_letters(&input, Distance::Edit).unwrap(), "abcd");
 }
@@ -1,17 +1,18 @@
 use regex::Regex;
-use std::collections::hash_map::Entry;
-use std::collections::HashMap;
-use std::collections::HashSet;
-use std::io::{Error, ErrorKind};
+use std::cmp::{max, min};
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::TryFrom;
+use std::fmt::Write;
+use std::io::{self, Error, ErrorKind};
 use std::str::FromStr;
 
 #[derive(Debug)]
 pub struct Claim {
     pub id: u32,
-    pub coord_x: u32,
-    pub coord_y: u32,
-    pub width: u32,
-    pub height: u32,
+    pub coord_x: u64,
+    pub coord_y: u64,
+    pub width: u64,
+    pub height: u64,
 }
 
 impl FromStr for Claim {
@@ -25,7 +26,7 @@ impl FromStr for Claim {
             Error::new(ErrorKind::InvalidData, "Invalid format")
         })?;
 
-        let numbers: Vec<u32> = groups
+        let numbers: Vec<u64> = groups
             .iter()
             .skip(1)
             .map(|val| {
@@ -36,8 +37,15 @@ impl FromStr for Claim {
             })
             .collect::<Result<_, _>>()?;
 
+        if numbers[1].checked_add(numbers[3]).is_none()
+            || numbers[2].checked_add(numbers[4]).is_none()
+        {
+            return Err(Error::new(ErrorKind::InvalidData, "Claim too large"));
+        }
+
         Ok(Claim {
-            id: numbers[0],
+            id: u32::try_from(numbers[0])
+                .map_err(|err| Error::new(ErrorKind::InvalidData, err))?,
             coord_x: numbers[1],
             coord_y: numbers[2],
             width: numbers[3],
@@ -46,39 +54,440 @@ impl FromStr for Claim {
     }
 }
 
-pub fn part1(claims: &[Claim]) -> u32 {
-    let mut overlap: HashMap<(u32, u32), bool> = HashMap::new();
-    claims.iter().for_each(|claim| {
-        for pos_x in claim.coord_x..(claim.coord_x + claim.width) {
-            for pos_y in claim.coord_y..(claim.coord_y + claim.height) {
-                overlap
-                    .entry((pos_x, pos_y))
-                    .and_modify(|val| *val = true)
-                    .or_insert(false);
+// Coordinates fit in a u64, so the area of any region fits in a u128
+pub struct Overlaps {
+    pub area: u128,
+    pub intact: BTreeSet<u32>,
+}
+
+pub fn find_overlaps(claims: &[Claim]) -> Overlaps {
+    let mut events = Vec::with_capacity(2 * claims.len());
+    for (idx, claim) in claims.iter().enumerate() {
+        if claim.width > 0 && claim.height > 0 {
+            events.push((claim.coord_x, true, idx));
+            events.push((claim.coord_x + claim.width, false, idx));
+        }
+    }
+    // Claims are half-open, so removals must come before insertions
+    events.sort_unstable();
+
+    let mut tree = SweepTree::new(claims);
+    let mut overlapped = vec![false; claims.len()];
+    let mut stamps = vec![0; claims.len()];
+    let mut next_stamp = 1;
+    let mut area = 0;
+    let mut prev_x = events.first().map_or(0, |(x, _, _)| *x);
+
+    for (x, is_start, idx) in events {
+        area += u128::from(tree.covered_twice()) * u128::from(x - prev_x);
+        prev_x = x;
+
+        let claim = &claims[idx];
+        let (max_cover, max_stamp) = tree.query(claim);
+        if is_start {
+            // Overlaps a claim that started earlier and is still active
+            if max_cover > 0 {
+                overlapped[idx] = true;
+            }
+            stamps[idx] = next_stamp;
+            tree.update(claim, 1, next_stamp);
+            next_stamp += 1;
+        } else {
+            // Overlaps a claim that started while this one was active
+            if max_stamp > stamps[idx] {
+                overlapped[idx] = true;
             }
+            tree.update(claim, -1, 0);
         }
-    });
-    overlap.values().filter(|&val| *val).count() as u32
+    }
+
+    let intact = claims
+        .iter()
+        .zip(overlapped.iter())
+        .filter(|(_, &overlapped)| !overlapped)
+        .map(|(claim, _)| claim.id)
+        .collect();
+
+    Overlaps { area, intact }
+}
+
+#[derive(Clone, Default)]
+struct SweepNode {
+    cover: i32,
+    max_cover: i32,
+    covered_once: u64,
+    covered_twice: u64,
+    stamp: usize,
+    max_stamp: usize,
+}
+
+// Segment tree over the compressed y coordinates of all claims
+struct SweepTree {
+    bounds: Vec<u64>,
+    nodes: Vec<SweepNode>,
+}
+
+impl SweepTree {
+    fn new(claims: &[Claim]) -> Self {
+        let mut bounds = Vec::with_capacity(2 * claims.len());
+        for claim in claims {
+            bounds.push(claim.coord_y);
+            bounds.push(claim.coord_y + claim.height);
+        }
+        bounds.sort_unstable();
+        bounds.dedup();
+        let nodes = vec![SweepNode::default(); 4 * bounds.len()];
+        SweepTree { bounds, nodes }
+    }
+
+    fn num_segments(&self) -> usize {
+        self.bounds.len().saturating_sub(1)
+    }
+
+    fn segments(&self, claim: &Claim) -> (usize, usize) {
+        let from = self.bounds.binary_search(&claim.coord_y).unwrap();
+        let to = self
+            .bounds
+            .binary_search(&(claim.coord_y + claim.height))
+            .unwrap();
+        (from, to)
+    }
+
+    fn covered_twice(&self) -> u64 {
+        if self.num_segments() == 0 {
+            0
+        } else {
+            self.nodes[1].covered_twice
+        }
+    }
+
+    fn update(&mut self, claim: &Claim, delta: i32, stamp: usize) {
+        let (from, to) = self.segments(claim);
+        let num_segments = self.num_segments();
+        self.update_node(1, 0, num_segments, from, to, delta, stamp);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn update_node(
+        &mut self,
+        node: usize,
+        lo: usize,
+        hi: usize,
+        from: usize,
+        to: usize,
+        delta: i32,
+        stamp: usize,
+    ) {
+        if to <= lo || hi <= from {
+            return;
+        }
+        if from <= lo && hi <= to {
+            self.nodes[node].cover += delta;
+            self.nodes[node].stamp = max(self.nodes[node].stamp, stamp);
+        } else {
+            let mid = (lo + hi) / 2;
+            self.update_node(2 * node, lo, mid, from, to, delta, stamp);
+            self.update_node(2 * node + 1, mid, hi, from, to, delta, stamp);
+        }
+        self.pull(node, lo, hi);
+    }
+
+    fn pull(&mut self, node: usize, lo: usize, hi: usize) {
+        let full = self.bounds[hi] - self.bounds[lo];
+        let (child_once, child_twice, child_cover, child_stamp) =
+            if hi - lo == 1 {
+                (0, 0, 0, 0)
+            } else {
+                let left = &self.nodes[2 * node];
+                let right = &self.nodes[2 * node + 1];
+                (
+                    left.covered_once + right.covered_once,
+                    left.covered_twice + right.covered_twice,
+                    max(left.max_cover, right.max_cover),
+                    max(left.max_stamp, right.max_stamp),
+                )
+            };
+
+        let current = &mut self.nodes[node];
+        current.max_cover = current.cover + child_cover;
+        current.max_stamp = max(current.stamp, child_stamp);
+        current.covered_once =
+            if current.cover > 0 { full } else { child_once };
+        current.covered_twice = match current.cover {
+            0 => child_twice,
+            1 => child_once,
+            _ => full,
+        };
+    }
+
+    // Returns the highest number of active claims covering any y coordinate
+    // of the given claim, and the latest stamp of any claim inserted there
+    fn query(&self, claim: &Claim) -> (i32, usize) {
+        let (from, to) = self.segments(claim);
+        self.query_node(1, 0, self.num_segments(), from, to)
+    }
+
+    fn query_node(
+        &self,
+        node: usize,
+        lo: usize,
+        hi: usize,
+        from: usize,
+        to: usize,
+    ) -> (i32, usize) {
+        if to <= lo || hi <= from {
+            return (0, 0);
+        }
+        let current = &self.nodes[node];
+        if from <= lo && hi <= to {
+            return (current.max_cover, current.max_stamp);
+        }
+        let mid = (lo + hi) / 2;
+        let (left_cover, left_stamp) =
+            self.query_node(2 * node, lo, mid, from, to);
+        let (right_cover, right_stamp) =
+            self.query_node(2 * node + 1, mid, hi, from, to);
+        (
+            current.cover + max(left_cover, right_cover),
+            max(current.stamp, max(left_stamp, right_stamp)),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub coord_x: u64,
+    pub coord_y: u64,
+    pub width: u64,
+    pub height: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    pub other_id: u32,
+    pub overlap: Rect,
+}
+
+pub struct ConflictGraph {
+    conflicts: BTreeMap<u32, Vec<Conflict>>,
 }
 
-pub fn part2(claims: &[Claim]) -> u32 {
-    let mut first_claim: HashMap<(u32, u32), u32> = HashMap::new();
-    let mut no_overlap: HashSet<u32> = HashSet::new();
-    claims.iter().for_each(|claim| {
-        no_overlap.insert(claim.id);
-        for pos_x in claim.coord_x..(claim.coord_x + claim.width) {
-            for pos_y in claim.coord_y..(claim.coord_y + claim.height) {
-                match first_claim.entry((pos_x, pos_y)) {
-                    Entry::Vacant(entry) => {
-                        entry.insert(claim.id);
-                    }
-                    Entry::Occupied(entry) => {
-                        no_overlap.remove(entry.get());
-                        no_overlap.remove(&claim.id);
-                    }
+impl ConflictGraph {
+    pub fn new(claims: &[Claim]) -> Self {
+        let mut conflicts: BTreeMap<u32, Vec<Conflict>> =
+            claims.iter().map(|claim| (claim.id, Vec::new())).collect();
+
+        let mut sorted: Vec<&Claim> = claims
+            .iter()
+            .filter(|claim| claim.width > 0 && claim.height > 0)
+            .collect();
+        sorted.sort_unstable_by_key(|claim| claim.coord_x);
+
+        for (idx, claim1) in sorted.iter().enumerate() {
+            let end_x = claim1.coord_x + claim1.width;
+            for claim2 in sorted[idx + 1..]
+                .iter()
+                .take_while(|claim2| claim2.coord_x < end_x)
+            {
+                if let Some(overlap) = overlap(claim1, claim2) {
+                    conflicts.entry(claim1.id).or_default().push(Conflict {
+                        other_id: claim2.id,
+                        overlap,
+                    });
+                    conflicts.entry(claim2.id).or_default().push(Conflict {
+                        other_id: claim1.id,
+                        overlap,
+                    });
                 }
             }
         }
-    });
-    *no_overlap.iter().next().unwrap_or(&0)
+
+        for claim_conflicts in conflicts.values_mut() {
+            claim_conflicts.sort_unstable_by_key(|conflict| conflict.other_id);
+        }
+
+        ConflictGraph { conflicts }
+    }
+
+    pub fn intact(&self) -> Vec<u32> {
+        self.conflicts
+            .iter()
+            .filter(|(_, conflicts)| conflicts.is_empty())
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    pub fn conflicts(&self, claim_id: u32) -> Option<&[Conflict]> {
+        self.conflicts
+            .get(&claim_id)
+            .map(|conflicts| &conflicts[..])
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph conflicts {\n");
+        for (id, conflicts) in self.conflicts.iter() {
+            if conflicts.is_empty() {
+                writeln!(dot, "    {} [style=filled, fillcolor=green];", id)
+                    .unwrap();
+            } else {
+                writeln!(dot, "    {};", id).unwrap();
+            }
+        }
+        for (id, conflicts) in self.conflicts.iter() {
+            for conflict in conflicts.iter().filter(|c| c.other_id > *id) {
+                let rect = &conflict.overlap;
+                writeln!(
+                    dot,
+                    "    {} -- {} [label=\"{},{}: {}x{}\"];",
+                    id,
+                    conflict.other_id,
+                    rect.coord_x,
+                    rect.coord_y,
+                    rect.width,
+                    rect.height
+                )
+                .unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn to_json(&self) -> String {
+        let intact: Vec<String> =
+            self.intact().iter().map(|id| id.to_string()).collect();
+        let claims: Vec<String> = self
+            .conflicts
+            .iter()
+            .map(|(id, conflicts)| {
+                let conflicts: Vec<String> = conflicts
+                    .iter()
+                    .map(|conflict| {
+                        let rect = &conflict.overlap;
+                        format!(
+                            "{{\"id\":{},\"x\":{},\"y\":{},\
+                             \"width\":{},\"height\":{}}}",
+                            conflict.other_id,
+                            rect.coord_x,
+                            rect.coord_y,
+                            rect.width,
+                            rect.height
+                        )
+                    })
+                    .collect();
+                format!(
+                    "{{\"id\":{},\"conflicts\":[{}]}}",
+                    id,
+                    conflicts.join(",")
+                )
+            })
+            .collect();
+        format!(
+            "{{\"intact\":[{}],\"claims\":[{}]}}",
+            intact.join(","),
+            claims.join(",")
+        )
+    }
+}
+
+fn overlap(claim1: &Claim, claim2: &Claim) -> Option<Rect> {
+    let start_x = max(claim1.coord_x, claim2.coord_x);
+    let start_y = max(claim1.coord_y, claim2.coord_y);
+    let end_x =
+        min(claim1.coord_x + claim1.width, claim2.coord_x + claim2.width);
+    let end_y = min(
+        claim1.coord_y + claim1.height,
+        claim2.coord_y + claim2.height,
+    );
+    if start_x < end_x && start_y < end_y {
+        Some(Rect {
+            coord_x: start_x,
+            coord_y: start_y,
+            width: end_x - start_x,
+            height: end_y - start_y,
+        })
+    } else {
+        None
+    }
+}
+
+const MAX_RENDER_PIXELS: u64 = 100_000_000;
+const INTACT_COLOR: [u8; 3] = [0, 200, 0];
+
+pub fn render_ppm<W: io::Write>(
+    claims: &[Claim],
+    out: &mut W,
+) -> io::Result<()> {
+    let width = claims
+        .iter()
+        .map(|claim| claim.coord_x + claim.width)
+        .max()
+        .unwrap_or(0);
+    let height = claims
+        .iter()
+        .map(|claim| claim.coord_y + claim.height)
+        .max()
+        .unwrap_or(0);
+    if width
+        .checked_mul(height)
+        .is_none_or(|size| size > MAX_RENDER_PIXELS)
+    {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Fabric too large to render",
+        ));
+    }
+    let (width, height) = (width as usize, height as usize);
+
+    // Count claims per square inch by accumulating a 2D difference grid
+    let mut counts = vec![0_i32; (width + 1) * (height + 1)];
+    for claim in claims.iter().filter(|c| c.width > 0 && c.height > 0) {
+        let (x0, y0) = (claim.coord_x as usize, claim.coord_y as usize);
+        let (x1, y1) = (x0 + claim.width as usize, y0 + claim.height as usize);
+        counts[y0 * (width + 1) + x0] += 1;
+        counts[y0 * (width + 1) + x1] -= 1;
+        counts[y1 * (width + 1) + x0] -= 1;
+        counts[y1 * (width + 1) + x1] += 1;
+    }
+    for y in 0..=height {
+        for x in 1..=width {
+            counts[y * (width + 1) + x] += counts[y * (width + 1) + x - 1];
+        }
+    }
+    for y in 1..=height {
+        for x in 0..=width {
+            counts[y * (width + 1) + x] += counts[(y - 1) * (width + 1) + x];
+        }
+    }
+    let max_count = *counts.iter().max().unwrap_or(&0);
+
+    let mut pixels = Vec::with_capacity(3 * width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let count = counts[y * (width + 1) + x];
+            let shade = (255 - 255 * count / max(max_count, 1)) as u8;
+            pixels.extend_from_slice(&[shade, shade, shade]);
+        }
+    }
+
+    let intact = find_overlaps(claims).intact;
+    for claim in claims.iter().filter(|c| intact.contains(&c.id)) {
+        for y in claim.coord_y..(claim.coord_y + claim.height) {
+            for x in claim.coord_x..(claim.coord_x + claim.width) {
+                let pos = 3 * (y as usize * width + x as usize);
+                pixels[pos..pos + 3].copy_from_slice(&INTACT_COLOR);
+            }
+        }
+    }
+
+    write!(out, "P6\n{} {}\n255\n", width, height)?;
+    out.write_all(&pixels)
+}
+
+pub fn part1(claims: &[Claim]) -> u128 {
+    find_overlaps(claims).area
+}
+
+pub fn part2(claims: &[Claim]) -> Option<u32> {
+    find_overlaps(claims).intact.into_iter().next()
 }
@@ -1,7 +1,7 @@
 use clap::{crate_description, App, Arg};
-use day03::{part1, part2, Claim};
-use std::fs::File;
-use std::io::{BufRead, BufReader, Error, ErrorKind};
+use day03::{part1, part2, render_ppm, Claim, ConflictGraph};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Error, ErrorKind};
 use std::process::exit;
 
 fn main() {
@@ -12,19 +12,54 @@ fn main() {
                 .required(true)
                 .index(1),
         )
+        .arg(
+            Arg::with_name("dot")
+                .long("dot")
+                .value_name("FILE")
+                .help("Writes the claim conflict graph in DOT format"),
+        )
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .value_name("FILE")
+                .help("Writes the claim conflict graph in JSON format"),
+        )
+        .arg(
+            Arg::with_name("render")
+                .long("render")
+                .value_name("FILE")
+                .help("Renders a heatmap of the claims as a PPM image"),
+        )
         .get_matches();
 
     println!(crate_description!());
     let input = read_input(args.value_of("INPUT").unwrap());
     println!("Part 1: {}", part1(&input));
-    println!("Part 2: {}", part2(&input));
+    match part2(&input) {
+        Some(claim_id) => println!("Part 2: {}", claim_id),
+        None => println!("Part 2: no intact claim"),
+    }
+
+    if args.is_present("dot") || args.is_present("json") {
+        let graph = ConflictGraph::new(&input);
+        if let Some(filename) = args.value_of("dot") {
+            write_output(filename, &graph.to_dot());
+        }
+        if let Some(filename) = args.value_of("json") {
+            write_output(filename, &graph.to_json());
+        }
+    }
+
+    if let Some(filename) = args.value_of("render") {
+        render_output(filename, &input);
+    }
 }
 
 fn read_input(filename: &str) -> Vec<Claim> {
     let file = match File::open(filename) {
         Ok(file) => file,
         Err(err) => {
-            println!("Failed to open file '{}': {}", filename, err.to_string());
+            println!("Failed to open file '{}': {}", filename, err);
             exit(2);
         }
     };
@@ -42,12 +77,24 @@ fn read_input(filename: &str) -> Vec<Claim> {
     {
         Ok(input) => input,
         Err(err) => {
-            println!(
-                "Failed to parse input file '{}': {}",
-                filename,
-                err.to_string()
-            );
+            println!("Failed to parse input file '{}': {}", filename, err);
             exit(3);
         }
     }
 }
+
+fn write_output(filename: &str, contents: &str) {
+    if let Err(err) = fs::write(filename, contents) {
+        println!("Failed to write output file '{}': {}", filename, err);
+        exit(5);
+    }
+}
+
+fn render_output(filename: &str, claims: &[Claim]) {
+    let result = File::create(filename)
+        .and_then(|file| render_ppm(claims, &mut BufWriter::new(file)));
+    if let Err(err) = result {
+        println!("Failed to render image '{}': {}", filename, err);
+        exit(5);
+    }
+}
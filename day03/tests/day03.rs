@@ -1,4 +1,7 @@
-use day03::{part1, part2, Claim};
+use day03::{
+    find_overlaps, part1, part2, render_ppm, Claim, Conflict, ConflictGraph,
+    Rect,
+};
 
 fn parse(input: &[&str]) -> Vec<Claim> {
     input.iter().map(|s| s.parse().unwrap()).collect()
@@ -46,5 +49,155 @@ fn examples_part2() {
         "#2 @ 3,1: 4x4",
         "#3 @ 5,5: 2x2",
     ];
-    assert_eq!(part2(&parse(&input)), 3);
+    assert_eq!(part2(&parse(&input)), Some(3));
+}
+
+#[test]
+fn overlaps_large_coordinates() {
+    let input = [
+        "#1 @ 0,0: 3000000x2000000",
+        "#2 @ 1000000,1000000: 3000000x3000000",
+        "#3 @ 5000000,0: 1000000x1000000",
+        "#4 @ 4000000,4000000: 1x1",
+    ];
+    let overlaps = find_overlaps(&parse(&input));
+    assert_eq!(overlaps.area, 2_000_000_000_000);
+    assert_eq!(overlaps.intact.into_iter().collect::<Vec<_>>(), vec![3, 4]);
+}
+
+#[test]
+fn overlaps_beyond_u64_area() {
+    let input = [
+        "#1 @ 0,0: 8589934592x8589934592",
+        "#2 @ 0,0: 8589934592x8589934592",
+        "#3 @ 8589934592,0: 1x1",
+    ];
+    let claims = parse(&input);
+    assert_eq!(part1(&claims), 1 << 66);
+    assert_eq!(part2(&claims), Some(3));
+}
+
+#[test]
+fn overlaps_contained_and_touching() {
+    let input = [
+        "#1 @ 0,0: 10x10",
+        "#2 @ 2,2: 2x2",
+        "#3 @ 10,0: 5x5",
+        "#4 @ 0,10: 5x5",
+        "#5 @ 20,20: 1x1",
+    ];
+    let overlaps = find_overlaps(&parse(&input));
+    assert_eq!(overlaps.area, 4);
+    assert_eq!(
+        overlaps.intact.into_iter().collect::<Vec<_>>(),
+        vec![3, 4, 5]
+    );
+}
+
+#[test]
+fn part2_no_intact_claim() {
+    let input = ["#1 @ 1,1: 2x2", "#2 @ 2,2: 2x2"];
+    assert_eq!(part2(&parse(&input)), None);
+}
+
+#[test]
+fn conflict_graph() {
+    let input = [
+        "#1 @ 1,3: 4x4",
+        "#2 @ 3,1: 4x4",
+        "#3 @ 5,5: 2x2",
+        "#4 @ 4,4: 2x2",
+    ];
+    let graph = ConflictGraph::new(&parse(&input));
+    assert_eq!(graph.intact(), Vec::<u32>::new());
+    assert_eq!(
+        graph.conflicts(4).unwrap(),
+        &[
+            Conflict {
+                other_id: 1,
+                overlap: Rect {
+                    coord_x: 4,
+                    coord_y: 4,
+                    width: 1,
+                    height: 2,
+                },
+            },
+            Conflict {
+                other_id: 2,
+                overlap: Rect {
+                    coord_x: 4,
+                    coord_y: 4,
+                    width: 2,
+                    height: 1,
+                },
+            },
+            Conflict {
+                other_id: 3,
+                overlap: Rect {
+                    coord_x: 5,
+                    coord_y: 5,
+                    width: 1,
+                    height: 1,
+                },
+            },
+        ]
+    );
+    assert_eq!(graph.conflicts(5), None);
+}
+
+#[test]
+fn conflict_graph_export() {
+    #[rustfmt::skip]
+    let input = [
+        "#1 @ 1,3: 4x4",
+        "#2 @ 3,1: 4x4",
+        "#3 @ 5,5: 2x2",
+    ];
+    let graph = ConflictGraph::new(&parse(&input));
+    assert_eq!(graph.intact(), vec![3]);
+    assert_eq!(
+        graph.to_dot(),
+        [
+            "graph conflicts {",
+            "    1;",
+            "    2;",
+            "    3 [style=filled, fillcolor=green];",
+            "    1 -- 2 [label=\"3,3: 2x2\"];",
+            "}\n",
+        ]
+        .join("\n")
+    );
+    assert_eq!(
+        graph.to_json(),
+        "{\"intact\":[3],\"claims\":[\
+         {\"id\":1,\"conflicts\":[\
+         {\"id\":2,\"x\":3,\"y\":3,\"width\":2,\"height\":2}]},\
+         {\"id\":2,\"conflicts\":[\
+         {\"id\":1,\"x\":3,\"y\":3,\"width\":2,\"height\":2}]},\
+         {\"id\":3,\"conflicts\":[]}]}"
+    );
+}
+
+#[test]
+fn render_heatmap() {
+    #[rustfmt::skip]
+    let input = [
+        "#1 @ 1,3: 4x4",
+        "#2 @ 3,1: 4x4",
+        "#3 @ 5,5: 2x2",
+    ];
+    let mut image = Vec::new();
+    render_ppm(&parse(&input), &mut image).unwrap();
+
+    let header = b"P6\n7 7\n255\n";
+    assert_eq!(&image[..header.len()], header);
+    let pixel = |x: usize, y: usize| {
+        let pos = header.len() + 3 * (y * 7 + x);
+        [image[pos], image[pos + 1], image[pos + 2]]
+    };
+    assert_eq!(image.len(), header.len() + 3 * 7 * 7);
+    assert_eq!(pixel(0, 0), [255, 255, 255]);
+    assert_eq!(pixel(1, 3), [128, 128, 128]);
+    assert_eq!(pixel(3, 3), [0, 0, 0]);
+    assert_eq!(pixel(5, 5), [0, 200, 0]);
 }
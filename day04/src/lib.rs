@@ -1,5 +1,7 @@
 use regex::Regex;
+use std::cmp::{max, min, Reverse};
 use std::collections::HashMap;
+use std::fmt::{self, Write};
 use std::io::{Error, ErrorKind};
 use std::str::FromStr;
 
@@ -7,10 +9,92 @@ type GuardID = u32;
 type Minute = usize;
 type MinuteCounters = HashMap<GuardID, Vec<u32>>;
 
-pub enum Event {
+const MINUTES_PER_DAY: i64 = 24 * 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+}
+
+impl Timestamp {
+    // Days since 1970-01-01 in the proleptic Gregorian calendar
+    fn days(&self) -> i64 {
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = i64::from(self.month);
+        let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2)
+            / 5
+            + i64::from(self.day)
+            - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4
+            - year_of_era / 100
+            + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    fn from_days(days: i64) -> Self {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524
+            - day_of_era / 146_096)
+            / 365;
+        let day_of_year = day_of_era
+            - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        };
+        Timestamp {
+            year: (year_of_era + era * 400 + i64::from(month <= 2)) as i32,
+            month: month as u32,
+            day: day as u32,
+            hour: 0,
+            minute: 0,
+        }
+    }
+
+    fn total_minutes(&self) -> i64 {
+        self.days() * MINUTES_PER_DAY
+            + i64::from(self.hour) * 60
+            + i64::from(self.minute)
+    }
+
+    fn is_valid(&self) -> bool {
+        let is_leap = (self.year % 4 == 0 && self.year % 100 != 0)
+            || self.year % 400 == 0;
+        let month_days = match self.month {
+            2 if is_leap => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            1..=12 => 31,
+            _ => return false,
+        };
+        self.day >= 1
+            && self.day <= month_days
+            && self.hour < 24
+            && self.minute < 60
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Action {
     ShiftBegin(GuardID),
-    FallAsleep(Minute),
-    WakeUp(Minute),
+    FallAsleep,
+    WakeUp,
+}
+
+pub struct Event {
+    pub time: Timestamp,
+    pub action: Action,
 }
 
 impl FromStr for Event {
@@ -18,110 +102,357 @@ impl FromStr for Event {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let event_regex =
-            Regex::new(r"^\[\d{4}-\d{2}-\d{2} \d{2}:(\d{2})\]\s+(.*)$")
+            Regex::new(r"^\[(\d{4})-(\d{2})-(\d{2}) (\d{2}):(\d{2})\]\s+(.*)$")
                 .unwrap();
 
         let parts = event_regex.captures(s).ok_or_else(|| {
             Error::new(ErrorKind::InvalidData, "Invalid format")
         })?;
 
-        let minute = parts
-            .get(1)
-            .unwrap()
-            .as_str()
-            .parse()
-            .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+        let numbers: Vec<u32> = parts
+            .iter()
+            .skip(1)
+            .take(5)
+            .map(|val| {
+                val.unwrap()
+                    .as_str()
+                    .parse()
+                    .map_err(|err| Error::new(ErrorKind::InvalidData, err))
+            })
+            .collect::<Result<_, _>>()?;
 
-        let event_str = parts.get(2).unwrap().as_str();
+        let time = Timestamp {
+            year: numbers[0] as i32,
+            month: numbers[1],
+            day: numbers[2],
+            hour: numbers[3],
+            minute: numbers[4],
+        };
+        if !time.is_valid() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Invalid timestamp",
+            ));
+        }
+
+        let event_str = parts.get(6).unwrap().as_str();
         let shift_regex = Regex::new(r"^Guard #(\d+) begins shift$").unwrap();
 
-        if let Some(shift_info) = shift_regex.captures(event_str) {
-            let guard_id = shift_info
-                .get(1)
-                .unwrap()
-                .as_str()
-                .parse()
-                .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
-            Ok(Event::ShiftBegin(guard_id))
-        } else {
-            match event_str {
-                "falls asleep" => Ok(Event::FallAsleep(minute)),
-                "wakes up" => Ok(Event::WakeUp(minute)),
-                _ => Err(Error::new(ErrorKind::InvalidData, "Unknown event")),
-            }
+        let action =
+            if let Some(shift_info) = shift_regex.captures(event_str) {
+                let guard_id =
+                    shift_info.get(1).unwrap().as_str().parse().map_err(
+                        |err| Error::new(ErrorKind::InvalidData, err),
+                    )?;
+                Action::ShiftBegin(guard_id)
+            } else {
+                match event_str {
+                    "falls asleep" => Action::FallAsleep,
+                    "wakes up" => Action::WakeUp,
+                    _ => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            "Unknown event",
+                        ))
+                    }
+                }
+            };
+
+        Ok(Event { time, action })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventError {
+    NoShift(usize),
+    AlreadyAsleep(usize),
+    NotAsleep(usize),
+    ShiftWhileAsleep(usize),
+}
+
+impl EventError {
+    pub fn index(&self) -> usize {
+        match *self {
+            EventError::NoShift(index)
+            | EventError::AlreadyAsleep(index)
+            | EventError::NotAsleep(index)
+            | EventError::ShiftWhileAsleep(index) => index,
         }
     }
 }
 
-pub fn process_events(events: Vec<Event>) -> MinuteCounters {
-    let mut sleep_counters: MinuteCounters = HashMap::new();
-    let mut curr_guard = None;
+impl fmt::Display for EventError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match self {
+            EventError::NoShift(_) => "event before any shift began",
+            EventError::AlreadyAsleep(_) => "guard is already asleep",
+            EventError::NotAsleep(_) => "guard wakes up without sleeping",
+            EventError::ShiftWhileAsleep(_) => {
+                "shift begins while previous guard is asleep"
+            }
+        };
+        write!(f, "Event {}: {}", self.index(), description)
+    }
+}
+
+impl std::error::Error for EventError {}
+
+#[derive(Clone, Debug)]
+pub struct Night {
+    pub date: Timestamp,
+    pub guard_id: GuardID,
+    pub asleep: [bool; 60],
+}
+
+pub fn process_events(
+    events: Vec<Event>,
+) -> Result<MinuteCounters, EventError> {
+    record_nights(events).map(|nights| count_sleep(&nights))
+}
+
+pub fn process_events_lenient(
+    events: Vec<Event>,
+) -> (MinuteCounters, Vec<EventError>) {
+    let (nights, anomalies) = record_nights_lenient(events);
+    (count_sleep(&nights), anomalies)
+}
+
+pub fn record_nights(events: Vec<Event>) -> Result<Vec<Night>, EventError> {
+    let (nights, anomalies) = record_nights_mode(events, false);
+    match anomalies.first() {
+        Some(err) => Err(*err),
+        None => Ok(nights),
+    }
+}
+
+pub fn record_nights_lenient(
+    events: Vec<Event>,
+) -> (Vec<Night>, Vec<EventError>) {
+    record_nights_mode(events, true)
+}
+
+fn record_nights_mode(
+    events: Vec<Event>,
+    lenient: bool,
+) -> (Vec<Night>, Vec<EventError>) {
+    let mut nights: Vec<Night> = Vec::new();
+    let mut anomalies = Vec::new();
     let mut sleep_start = None;
 
-    for event in events {
-        match event {
-            Event::ShiftBegin(guard_id) => {
-                assert!(sleep_start.is_none());
-                curr_guard = Some(guard_id);
+    let mut events: Vec<(usize, Event)> =
+        events.into_iter().enumerate().collect();
+    events.sort_by_key(|(_, event)| event.time);
+
+    for (index, event) in events {
+        let anomaly = match (event.action, nights.last_mut(), sleep_start) {
+            (Action::ShiftBegin(guard_id), _, _) => {
+                // Shifts starting late in the evening cover the next midnight
+                let days = event.time.days() + i64::from(event.time.hour >= 12);
+                nights.push(Night {
+                    date: Timestamp::from_days(days),
+                    guard_id,
+                    asleep: [false; 60],
+                });
+                // The unfinished sleep is dropped in lenient mode
+                sleep_start
+                    .take()
+                    .map(|_| EventError::ShiftWhileAsleep(index))
+            }
+            (_, None, _) => Some(EventError::NoShift(index)),
+            (Action::FallAsleep, _, Some(_)) => {
+                Some(EventError::AlreadyAsleep(index))
             }
-            Event::FallAsleep(sleep_minute) => {
-                assert!(curr_guard.is_some() && sleep_start.is_none());
-                sleep_start = Some(sleep_minute);
+            (Action::FallAsleep, _, None) => {
+                sleep_start = Some(event.time);
+                None
             }
-            Event::WakeUp(wake_minute) => {
-                let guard_id = curr_guard.unwrap();
-                let sleep_min = sleep_start.unwrap();
-                for item in sleep_counters
-                    .entry(guard_id)
-                    .or_insert_with(|| vec![0; 60])
-                    .iter_mut()
-                    .take(wake_minute)
-                    .skip(sleep_min)
-                {
-                    *item += 1;
+            (Action::WakeUp, _, None) => Some(EventError::NotAsleep(index)),
+            (Action::WakeUp, Some(night), Some(sleep_time)) => {
+                for minute in midnight_minutes(&sleep_time, &event.time) {
+                    night.asleep[minute] = true;
                 }
                 sleep_start = None;
+                None
+            }
+        };
+
+        if let Some(anomaly) = anomaly {
+            anomalies.push(anomaly);
+            if !lenient {
+                break;
             }
         }
     }
 
-    sleep_counters
+    (nights, anomalies)
 }
 
-pub fn part1(sleep_counters: &MinuteCounters) -> u32 {
-    match sleep_counters
-        .iter()
-        .map(|(guard_id, counters)| (*guard_id, counters.iter().sum::<u32>()))
-        .max_by_key(|(_, total_sleep)| *total_sleep)
-    {
-        None => 0,
-        Some((guard_id, _)) => {
-            let (sleepiest_minute, _) = sleep_counters
-                .get(&guard_id)
-                .unwrap()
-                .iter()
-                .enumerate()
-                .max_by_key(|(_, sleeping_count)| *sleeping_count)
-                .unwrap();
-            guard_id * sleepiest_minute as u32
+pub fn count_sleep(nights: &[Night]) -> MinuteCounters {
+    let mut sleep_counters: MinuteCounters = HashMap::new();
+    for night in nights.iter().filter(|n| n.asleep.iter().any(|&a| a)) {
+        let counters = sleep_counters
+            .entry(night.guard_id)
+            .or_insert_with(|| vec![0; 60]);
+        for (counter, &asleep) in counters.iter_mut().zip(night.asleep.iter()) {
+            *counter += asleep as u32;
         }
     }
+    sleep_counters
 }
 
-pub fn part2(sleep_counters: &MinuteCounters) -> u32 {
-    match sleep_counters
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GuardSummary {
+    pub guard_id: GuardID,
+    pub total_sleep: u32,
+    pub sleepiest_minute: Option<Minute>,
+    pub sleepiest_count: u32,
+}
+
+// Ordered by total sleep, then by guard ID; ties for the sleepiest minute are
+// broken by the earliest minute
+fn summarize_guards(sleep_counters: &MinuteCounters) -> Vec<GuardSummary> {
+    let mut guards: Vec<GuardSummary> = sleep_counters
         .iter()
-        .map(|(guard_id, counters)| {
-            let (sleepiest_minute, sleeping_count) = counters
+        .map(|(&guard_id, counters)| {
+            let (sleepiest_minute, sleepiest_count) = counters
                 .iter()
                 .enumerate()
-                .max_by_key(|(_, sleeping_count)| *sleeping_count)
+                .max_by_key(|(minute, count)| (**count, Reverse(*minute)))
+                .map(|(minute, count)| (minute, *count))
                 .unwrap();
-            (*guard_id, sleepiest_minute as u32, sleeping_count)
+            GuardSummary {
+                guard_id,
+                total_sleep: counters.iter().sum(),
+                sleepiest_minute: if sleepiest_count > 0 {
+                    Some(sleepiest_minute)
+                } else {
+                    None
+                },
+                sleepiest_count,
+            }
         })
-        .max_by_key(|(_, _, sleeping_count)| *sleeping_count)
-    {
-        None => 0,
-        Some((guard_id, sleepiest_minute, _)) => guard_id * sleepiest_minute,
+        .collect();
+    guards.sort_by_key(|guard| (Reverse(guard.total_sleep), guard.guard_id));
+    guards
+}
+
+pub struct SleepReport<'a> {
+    nights: &'a [Night],
+    guards: Vec<GuardSummary>,
+}
+
+impl<'a> SleepReport<'a> {
+    pub fn new(nights: &'a [Night]) -> Self {
+        // Guards that never fall asleep are listed too
+        let mut sleep_counters = count_sleep(nights);
+        for night in nights {
+            sleep_counters
+                .entry(night.guard_id)
+                .or_insert_with(|| vec![0; 60]);
+        }
+        SleepReport {
+            nights,
+            guards: summarize_guards(&sleep_counters),
+        }
     }
+
+    pub fn guards(&self) -> &[GuardSummary] {
+        &self.guards
+    }
+
+    pub fn timeline(&self) -> String {
+        let id_width = self
+            .nights
+            .iter()
+            .map(|night| night.guard_id.to_string().len() + 1)
+            .max()
+            .unwrap_or(0)
+            .max(2);
+        let indent = " ".repeat(7 + id_width + 2);
+        let tens: String = (0..60).map(|m| (b'0' + m / 10) as char).collect();
+        let ones: String = (0..60).map(|m| (b'0' + m % 10) as char).collect();
+
+        let mut timeline = format!(
+            "{:<7}{:<width$}  Minute\n{}{}\n{}{}\n",
+            "Date",
+            "ID",
+            indent,
+            tens,
+            indent,
+            ones,
+            width = id_width
+        );
+        for night in self.nights {
+            let minutes: String = night
+                .asleep
+                .iter()
+                .map(|&asleep| if asleep { '#' } else { '.' })
+                .collect();
+            writeln!(
+                timeline,
+                "{:02}-{:02}  {:<width$}  {}",
+                night.date.month,
+                night.date.day,
+                format!("#{}", night.guard_id),
+                minutes,
+                width = id_width
+            )
+            .unwrap();
+        }
+        timeline
+    }
+
+    pub fn guard_table(&self) -> String {
+        let mut table = String::from("Rank  Guard     Asleep  Minute  Count\n");
+        for (rank, guard) in self.guards.iter().enumerate() {
+            let minute = guard
+                .sleepiest_minute
+                .map_or("-".to_string(), |minute| minute.to_string());
+            writeln!(
+                table,
+                "{:>4}  {:<8}  {:>6}  {:>6}  {:>5}",
+                rank + 1,
+                format!("#{}", guard.guard_id),
+                guard.total_sleep,
+                minute,
+                guard.sleepiest_count
+            )
+            .unwrap();
+        }
+        table
+    }
+}
+
+// Minutes of the midnight hour (00:00 - 00:59) between start and end
+fn midnight_minutes(start: &Timestamp, end: &Timestamp) -> Vec<Minute> {
+    let (from, to) = (start.total_minutes(), end.total_minutes());
+    (start.days()..=end.days())
+        .flat_map(|day| {
+            let midnight = day * MINUTES_PER_DAY;
+            (max(from, midnight)..min(to, midnight + 60))
+                .map(move |minute| (minute - midnight) as Minute)
+        })
+        .collect()
+}
+
+fn strategy_result(guard: Option<&GuardSummary>) -> u32 {
+    guard
+        .and_then(|guard| {
+            guard
+                .sleepiest_minute
+                .map(|minute| guard.guard_id * minute as u32)
+        })
+        .unwrap_or(0)
+}
+
+pub fn part1(sleep_counters: &MinuteCounters) -> u32 {
+    strategy_result(summarize_guards(sleep_counters).first())
+}
+
+pub fn part2(sleep_counters: &MinuteCounters) -> u32 {
+    strategy_result(
+        summarize_guards(sleep_counters).iter().min_by_key(|guard| {
+            (Reverse(guard.sleepiest_count), guard.guard_id)
+        }),
+    )
 }
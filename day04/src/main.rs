@@ -1,5 +1,8 @@
 use clap::{crate_description, App, Arg};
-use day04::{part1, part2, process_events, Event};
+use day04::{
+    count_sleep, part1, part2, record_nights, record_nights_lenient, Event,
+    SleepReport,
+};
 use std::fs::File;
 use std::io::{BufRead, BufReader, Error, ErrorKind};
 use std::process::exit;
@@ -12,38 +15,65 @@ fn main() {
                 .required(true)
                 .index(1),
         )
+        .arg(
+            Arg::with_name("lenient")
+                .long("lenient")
+                .help("Skips and reports invalid events instead of failing"),
+        )
+        .arg(
+            Arg::with_name("report")
+                .long("report")
+                .help("Prints the sleep timeline and a ranking of guards"),
+        )
         .get_matches();
 
     println!(crate_description!());
     let events = read_input(args.value_of("INPUT").unwrap());
-    let sleep_counters = process_events(events);
+    let nights = if args.is_present("lenient") {
+        let (nights, anomalies) = record_nights_lenient(events);
+        for anomaly in anomalies {
+            println!("Skipped invalid event: {}", anomaly);
+        }
+        nights
+    } else {
+        match record_nights(events) {
+            Ok(nights) => nights,
+            Err(err) => {
+                println!("Invalid input: {}", err);
+                exit(5);
+            }
+        }
+    };
+    let sleep_counters = count_sleep(&nights);
     println!("Part 1: {}", part1(&sleep_counters));
     println!("Part 2: {}", part2(&sleep_counters));
+
+    if args.is_present("report") {
+        let report = SleepReport::new(&nights);
+        println!();
+        print!("{}", report.timeline());
+        println!();
+        print!("{}", report.guard_table());
+    }
 }
 
 fn read_input(filename: &str) -> Vec<Event> {
     let file = match File::open(filename) {
         Ok(file) => file,
         Err(err) => {
-            println!("Failed to open file '{}': {}", filename, err.to_string());
+            println!("Failed to open file '{}': {}", filename, err);
             exit(2);
         }
     };
 
-    let mut lines: Vec<_> = match BufReader::new(file).lines().collect() {
+    let lines: Vec<_> = match BufReader::new(file).lines().collect() {
         Ok(lines) => lines,
         Err(err) => {
-            println!(
-                "Failed to read input file '{}': {}",
-                filename,
-                err.to_string()
-            );
+            println!("Failed to read input file '{}': {}", filename, err);
             exit(3);
         }
     };
 
-    lines.sort();
-
     match lines
         .iter()
         .map(|line| {
@@ -54,11 +84,7 @@ fn read_input(filename: &str) -> Vec<Event> {
     {
         Ok(input) => input,
         Err(err) => {
-            println!(
-                "Failed to parse input file '{}': {}",
-                filename,
-                err.to_string()
-            );
+            println!("Failed to parse input file '{}': {}", filename, err);
             exit(4);
         }
     }
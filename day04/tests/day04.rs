@@ -1,12 +1,13 @@
-use day04::{part1, part2, process_events, Event};
+use day04::{
+    part1, part2, process_events, process_events_lenient, record_nights, Event,
+    EventError, GuardSummary, SleepReport,
+};
 
 fn parse_input(input: &str) -> Vec<Event> {
     input.lines().map(|s| s.trim().parse().unwrap()).collect()
 }
 
-#[test]
-fn example() {
-    let input = "[1518-11-01 00:00] Guard #10 begins shift
+const EXAMPLE: &str = "[1518-11-01 00:00] Guard #10 begins shift
                  [1518-11-01 00:05] falls asleep
                  [1518-11-01 00:25] wakes up
                  [1518-11-01 00:30] falls asleep
@@ -24,7 +25,185 @@ fn example() {
                  [1518-11-05 00:45] falls asleep
                  [1518-11-05 00:55] wakes up";
 
-    let sleep_counters = process_events(parse_input(&input));
+#[test]
+fn example() {
+    let sleep_counters = process_events(parse_input(EXAMPLE)).unwrap();
     assert_eq!(part1(&sleep_counters), 240);
     assert_eq!(part2(&sleep_counters), 4455);
 }
+
+#[test]
+fn shuffled_example() {
+    let input = "[1518-11-04 00:46] wakes up
+                 [1518-11-01 00:25] wakes up
+                 [1518-11-05 00:55] wakes up
+                 [1518-11-01 23:58] Guard #99 begins shift
+                 [1518-11-03 00:24] falls asleep
+                 [1518-11-01 00:05] falls asleep
+                 [1518-11-02 00:50] wakes up
+                 [1518-11-05 00:03] Guard #99 begins shift
+                 [1518-11-01 00:30] falls asleep
+                 [1518-11-04 00:02] Guard #99 begins shift
+                 [1518-11-01 00:55] wakes up
+                 [1518-11-03 00:05] Guard #10 begins shift
+                 [1518-11-02 00:40] falls asleep
+                 [1518-11-03 00:29] wakes up
+                 [1518-11-04 00:36] falls asleep
+                 [1518-11-01 00:00] Guard #10 begins shift
+                 [1518-11-05 00:45] falls asleep";
+
+    let sleep_counters = process_events(parse_input(input)).unwrap();
+    assert_eq!(part1(&sleep_counters), 240);
+    assert_eq!(part2(&sleep_counters), 4455);
+}
+
+#[test]
+fn sleep_clamped_to_midnight_hour() {
+    let input = "[1518-02-28 23:50] Guard #7 begins shift
+                 [1518-02-28 23:55] falls asleep
+                 [1518-03-01 00:03] wakes up
+                 [1518-03-01 00:58] falls asleep
+                 [1518-03-01 01:10] wakes up";
+
+    let sleep_counters = process_events(parse_input(input)).unwrap();
+    let counters = &sleep_counters[&7];
+    assert_eq!(counters.iter().sum::<u32>(), 5);
+    assert_eq!(counters[0..3], [1, 1, 1]);
+    assert_eq!(counters[58..60], [1, 1]);
+}
+
+#[test]
+fn invalid_timestamp() {
+    assert!("[1518-02-29 00:00] wakes up".parse::<Event>().is_err());
+    assert!("[1518-11-01 24:00] wakes up".parse::<Event>().is_err());
+}
+
+#[test]
+fn invalid_events() {
+    let input = "[1518-11-02 00:00] Guard #10 begins shift
+                 [1518-11-01 00:05] falls asleep";
+    assert_eq!(
+        process_events(parse_input(input)).err(),
+        Some(EventError::NoShift(1))
+    );
+
+    let input = "[1518-11-01 00:00] Guard #10 begins shift
+                 [1518-11-01 00:05] falls asleep
+                 [1518-11-01 00:08] falls asleep";
+    assert_eq!(
+        process_events(parse_input(input)).err(),
+        Some(EventError::AlreadyAsleep(2))
+    );
+
+    let input = "[1518-11-01 00:00] Guard #10 begins shift
+                 [1518-11-01 00:05] wakes up";
+    assert_eq!(
+        process_events(parse_input(input)).err(),
+        Some(EventError::NotAsleep(1))
+    );
+
+    let input = "[1518-11-01 00:00] Guard #10 begins shift
+                 [1518-11-01 00:05] falls asleep
+                 [1518-11-02 00:00] Guard #99 begins shift";
+    assert_eq!(
+        process_events(parse_input(input)).err(),
+        Some(EventError::ShiftWhileAsleep(2))
+    );
+}
+
+#[test]
+fn lenient_processing() {
+    let input = "[1518-11-01 00:03] wakes up
+                 [1518-11-01 00:00] Guard #10 begins shift
+                 [1518-11-01 00:05] falls asleep
+                 [1518-11-01 00:07] falls asleep
+                 [1518-11-01 00:10] wakes up
+                 [1518-11-01 00:20] wakes up
+                 [1518-10-31 23:50] wakes up";
+
+    let (sleep_counters, anomalies) =
+        process_events_lenient(parse_input(input));
+    assert_eq!(
+        anomalies,
+        vec![
+            EventError::NoShift(6),
+            EventError::NotAsleep(0),
+            EventError::AlreadyAsleep(3),
+            EventError::NotAsleep(5),
+        ]
+    );
+    assert_eq!(sleep_counters[&10].iter().sum::<u32>(), 5);
+    assert_eq!(
+        anomalies[1].to_string(),
+        "Event 0: guard wakes up without sleeping"
+    );
+}
+
+#[test]
+fn report_timeline() {
+    let nights = record_nights(parse_input(EXAMPLE)).unwrap();
+    let report = SleepReport::new(&nights);
+    let expected = [
+        "Date   ID   Minute",
+        "            000000000011111111112222222222333333333344444444445555555555",
+        "            012345678901234567890123456789012345678901234567890123456789",
+        "11-01  #10  .....####################.....#########################.....",
+        "11-02  #99  ........................................##########..........",
+        "11-03  #10  ........................#####...............................",
+        "11-04  #99  ....................................##########..............",
+        "11-05  #99  .............................................##########.....",
+    ];
+    assert_eq!(report.timeline(), expected.join("\n") + "\n");
+}
+
+#[test]
+fn report_guards() {
+    let nights = record_nights(parse_input(EXAMPLE)).unwrap();
+    let report = SleepReport::new(&nights);
+    assert_eq!(
+        report.guards(),
+        &[
+            GuardSummary {
+                guard_id: 10,
+                total_sleep: 50,
+                sleepiest_minute: Some(24),
+                sleepiest_count: 2,
+            },
+            GuardSummary {
+                guard_id: 99,
+                total_sleep: 30,
+                sleepiest_minute: Some(45),
+                sleepiest_count: 3,
+            },
+        ]
+    );
+}
+
+#[test]
+fn sleepiest_minute_ties() {
+    let input = "[1518-03-01 00:00] Guard #10 begins shift
+                 [1518-03-01 00:05] falls asleep
+                 [1518-03-01 00:06] wakes up
+                 [1518-03-02 00:00] Guard #10 begins shift
+                 [1518-03-02 00:09] falls asleep
+                 [1518-03-02 00:10] wakes up
+                 [1518-03-03 00:00] Guard #7 begins shift
+                 [1518-03-03 00:30] falls asleep
+                 [1518-03-03 00:31] wakes up
+                 [1518-03-04 00:00] Guard #7 begins shift
+                 [1518-03-04 00:20] falls asleep
+                 [1518-03-04 00:21] wakes up
+                 [1518-03-05 00:00] Guard #3 begins shift";
+    let nights = record_nights(parse_input(input)).unwrap();
+    let report = SleepReport::new(&nights);
+    let minutes: Vec<_> = report
+        .guards()
+        .iter()
+        .map(|guard| (guard.guard_id, guard.sleepiest_minute))
+        .collect();
+    assert_eq!(minutes, vec![(7, Some(20)), (10, Some(5)), (3, None)]);
+
+    let sleep_counters = process_events(parse_input(input)).unwrap();
+    assert_eq!(part1(&sleep_counters), 7 * 20);
+    assert_eq!(part2(&sleep_counters), 7 * 20);
+}
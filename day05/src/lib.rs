@@ -1,48 +1,302 @@
-pub fn reduced_polymer_len(polymer: &str) -> usize {
-    reduced_len(polymer.chars())
+use std::cmp::max;
+use std::collections::{BTreeSet, HashSet};
+use std::io::{self, Error, ErrorKind, Read};
+use std::str;
+use std::thread;
+
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reduction {
+    pub polymer: String,
+    pub removed: usize,
+    // Lowercase unit types of the original polymer, in order
+    pub unit_types: Vec<char>,
+    // Positions of the two units removed by every reaction, in the order the
+    // reactions happen, only kept when tracing
+    pub trace: Option<Vec<(usize, usize)>>,
+}
+
+impl Reduction {
+    pub fn len(&self) -> usize {
+        self.polymer.chars().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.polymer.is_empty()
+    }
+}
+
+pub struct ReactionTable {
+    pairs: HashSet<(char, char)>,
 }
 
-fn reduced_len<I>(polymer: I) -> usize
+impl ReactionTable {
+    pub fn new<I>(pairs: I) -> Self
+    where
+        I: IntoIterator<Item = (char, char)>,
+    {
+        let pairs = pairs
+            .into_iter()
+            .flat_map(|(unit1, unit2)| vec![(unit1, unit2), (unit2, unit1)])
+            .collect();
+        ReactionTable { pairs }
+    }
+
+    pub fn reacts(&self, unit1: char, unit2: char) -> bool {
+        self.pairs.contains(&(unit1, unit2))
+    }
+}
+
+pub fn opposite_polarity(unit1: char, unit2: char) -> bool {
+    unit1 != unit2 && unit1.eq_ignore_ascii_case(&unit2)
+}
+
+struct Reducer<F> {
+    reacts: F,
+    reduced: Vec<char>,
+    removed: usize,
+    unit_types: BTreeSet<char>,
+    // Original position of every unit in reduced, only kept when tracing
+    positions: Option<Vec<usize>>,
+    trace: Vec<(usize, usize)>,
+    next_position: usize,
+}
+
+impl<F> Reducer<F>
 where
-    I: Iterator<Item = char>,
+    F: Fn(char, char) -> bool,
 {
-    let mut reduced = Vec::new();
-    let mut last_unit: Option<char> = None;
+    fn new(reacts: F, tracing: bool) -> Self {
+        Reducer {
+            reacts,
+            reduced: Vec::new(),
+            removed: 0,
+            unit_types: BTreeSet::new(),
+            positions: if tracing { Some(Vec::new()) } else { None },
+            trace: Vec::new(),
+            next_position: 0,
+        }
+    }
 
-    for unit in polymer {
-        if let Some(last) = last_unit {
-            if last.to_ascii_lowercase() == unit.to_ascii_lowercase()
-                && last != unit
-            {
-                last_unit = reduced.pop();
-            } else {
-                reduced.push(last);
-                last_unit = Some(unit);
+    fn push(&mut self, unit: char) {
+        let position = self.next_position;
+        self.next_position += 1;
+        self.unit_types.insert(unit.to_ascii_lowercase());
+
+        match self.reduced.last() {
+            Some(&last) if (self.reacts)(last, unit) => {
+                self.reduced.pop();
+                self.removed += 2;
+                if let Some(positions) = self.positions.as_mut() {
+                    self.trace.push((positions.pop().unwrap(), position));
+                }
             }
+            _ => {
+                self.reduced.push(unit);
+                if let Some(positions) = self.positions.as_mut() {
+                    positions.push(position);
+                }
+            }
+        }
+    }
+
+    fn finish(self) -> Reduction {
+        let trace = if self.positions.is_some() {
+            Some(self.trace)
         } else {
-            last_unit = Some(unit);
+            None
+        };
+        Reduction {
+            polymer: self.reduced.into_iter().collect(),
+            removed: self.removed,
+            unit_types: self.unit_types.into_iter().collect(),
+            trace,
         }
     }
+}
 
-    if let Some(last) = last_unit {
-        reduced.push(last);
+pub fn reduce<I, F>(polymer: I, reacts: F) -> Reduction
+where
+    I: IntoIterator<Item = char>,
+    F: Fn(char, char) -> bool,
+{
+    let mut reducer = Reducer::new(reacts, false);
+    for unit in polymer {
+        reducer.push(unit);
     }
+    reducer.finish()
+}
 
-    reduced.len()
+pub fn reduce_with_trace<I, F>(polymer: I, reacts: F) -> Reduction
+where
+    I: IntoIterator<Item = char>,
+    F: Fn(char, char) -> bool,
+{
+    let mut reducer = Reducer::new(reacts, true);
+    for unit in polymer {
+        reducer.push(unit);
+    }
+    reducer.finish()
+}
+
+pub fn reduce_reader<R, F>(reader: R, reacts: F) -> io::Result<Reduction>
+where
+    R: Read,
+    F: Fn(char, char) -> bool,
+{
+    reduce_reader_mode(reader, reacts, false)
+}
+
+pub fn reduce_reader_with_trace<R, F>(
+    reader: R,
+    reacts: F,
+) -> io::Result<Reduction>
+where
+    R: Read,
+    F: Fn(char, char) -> bool,
+{
+    reduce_reader_mode(reader, reacts, true)
+}
+
+fn reduce_reader_mode<R, F>(
+    mut reader: R,
+    reacts: F,
+    tracing: bool,
+) -> io::Result<Reduction>
+where
+    R: Read,
+    F: Fn(char, char) -> bool,
+{
+    let mut reducer = Reducer::new(reacts, tracing);
+    let mut buffer = vec![0; READ_BUFFER_SIZE];
+    let mut pending = 0;
+
+    loop {
+        let filled = match reader.read(&mut buffer[pending..]) {
+            Ok(0) if pending > 0 => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "Incomplete UTF-8 sequence",
+                ))
+            }
+            Ok(0) => break,
+            Ok(read) => pending + read,
+            Err(ref err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+
+        // A multi-byte character may be split across reads
+        let valid = match str::from_utf8(&buffer[..filled]) {
+            Ok(units) => units.len(),
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(err) => return Err(Error::new(ErrorKind::InvalidData, err)),
+        };
+        for unit in str::from_utf8(&buffer[..valid]).unwrap().chars() {
+            reducer.push(unit);
+        }
+        buffer.copy_within(valid..filled, 0);
+        pending = filled - valid;
+    }
+
+    Ok(reducer.finish())
+}
+
+pub fn reduced_polymer_len(polymer: &str) -> usize {
+    reduce(polymer.chars(), opposite_polarity).len()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnitImpact {
+    pub unit: char,
+    pub reduced_len: usize,
+    pub reactions: usize,
+}
+
+pub struct ImpactReport {
+    units: Vec<UnitImpact>,
+    trace: Option<Vec<(usize, usize)>>,
+}
+
+impl ImpactReport {
+    // Units are removed from the reduced polymer and the rest is reduced
+    // again by the same rule; this matches removing them from the original
+    // polymer when every unit reacts only with its counterpart of the same
+    // type, as with opposite polarities
+    pub fn new<F>(reduction: &Reduction, reacts: F) -> Self
+    where
+        F: Fn(char, char) -> bool + Sync,
+    {
+        let reduced = &reduction.polymer;
+        let reacts = &reacts;
+        let impact = move |unit: char| {
+            let removal = reduce(
+                reduced.chars().filter(|u| u.to_ascii_lowercase() != unit),
+                reacts,
+            );
+            UnitImpact {
+                unit,
+                reduced_len: removal.len(),
+                reactions: removal.removed / 2,
+            }
+        };
+
+        // Unit types are split evenly between one thread per available core
+        let num_threads =
+            thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk_size =
+            max(1, reduction.unit_types.len().div_ceil(num_threads));
+        let units = thread::scope(|scope| {
+            let chunks: Vec<_> = reduction
+                .unit_types
+                .chunks(chunk_size)
+                .map(|units| {
+                    scope.spawn(move || {
+                        units
+                            .iter()
+                            .map(|&unit| impact(unit))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            chunks
+                .into_iter()
+                .flat_map(|chunk| chunk.join().unwrap())
+                .collect()
+        });
+
+        ImpactReport {
+            units,
+            trace: reduction.trace.clone(),
+        }
+    }
+
+    pub fn units(&self) -> &[UnitImpact] {
+        &self.units
+    }
+
+    pub fn trace(&self) -> Option<&[(usize, usize)]> {
+        self.trace.as_deref()
+    }
+
+    pub fn shortest(&self) -> Option<&UnitImpact> {
+        self.units.iter().min_by_key(|impact| impact.reduced_len)
+    }
 }
 
 pub fn shortest_reduction(polymer: &str) -> usize {
-    let mut unique_units: Vec<char> = polymer.to_lowercase().chars().collect();
-    unique_units.sort_unstable();
-    unique_units.dedup();
-
-    unique_units
-        .iter()
-        .map(|&unit| {
-            reduced_len(
-                polymer.chars().filter(|u| u.to_ascii_lowercase() != unit),
-            )
-        })
-        .min()
-        .unwrap_or(0)
+    // Removing a unit type and reducing gives the same result whether
+    // it's done on the original or on the already reduced polymer
+    let reduction = reduce(polymer.chars(), opposite_polarity);
+    ImpactReport::new(&reduction, opposite_polarity)
+        .shortest()
+        .map_or(0, |impact| impact.reduced_len)
+}
+
+pub fn shortest_reduction_reader<R: Read>(reader: R) -> io::Result<usize> {
+    let reduction = reduce_reader(reader, opposite_polarity)?;
+    Ok(ImpactReport::new(&reduction, opposite_polarity)
+        .shortest()
+        .map_or(0, |impact| impact.reduced_len))
 }
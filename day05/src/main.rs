@@ -1,7 +1,10 @@
 use clap::{crate_description, App, Arg};
-use day05::{reduced_polymer_len, shortest_reduction};
+use day05::{
+    opposite_polarity, reduce_reader, reduce_reader_with_trace, ImpactReport,
+    Reduction,
+};
 use std::fs::File;
-use std::io::Read;
+use std::io::BufReader;
 use std::process::exit;
 
 fn main() {
@@ -12,32 +15,61 @@ fn main() {
                 .required(true)
                 .index(1),
         )
+        .arg(Arg::with_name("report").long("report").help(
+            "Prints the impact of removing each unit type and every \
+                     reaction",
+        ))
         .get_matches();
 
     println!(crate_description!());
-    let polymer = read_input(args.value_of("INPUT").unwrap());
-    println!("Part 1: {}", reduced_polymer_len(&polymer));
-    println!("Part 2: {}", shortest_reduction(&polymer));
+    let reduction =
+        read_input(args.value_of("INPUT").unwrap(), args.is_present("report"));
+    println!("Part 1: {}", reduction.len());
+    let report = ImpactReport::new(&reduction, opposite_polarity);
+    match report.shortest() {
+        Some(impact) => println!("Part 2: {}", impact.reduced_len),
+        None => println!("Part 2: 0"),
+    }
+
+    if args.is_present("report") {
+        println!();
+        println!("Unit  Length  Reactions");
+        for impact in report.units() {
+            println!(
+                "{:>4}  {:>6}  {:>9}",
+                impact.unit, impact.reduced_len, impact.reactions
+            );
+        }
+
+        println!();
+        println!("Reaction  Positions");
+        for (index, (first, second)) in
+            report.trace().unwrap_or(&[]).iter().enumerate()
+        {
+            println!("{:>8}  {} {}", index + 1, first, second);
+        }
+    }
 }
 
-fn read_input(filename: &str) -> String {
-    let mut file = match File::open(filename) {
+fn read_input(filename: &str, tracing: bool) -> Reduction {
+    let file = match File::open(filename) {
         Ok(file) => file,
         Err(err) => {
-            println!("Failed to open file '{}': {}", filename, err.to_string());
+            println!("Failed to open file '{}': {}", filename, err);
             exit(2);
         }
     };
 
-    let mut contents = String::new();
-    match file.read_to_string(&mut contents) {
-        Ok(_) => contents,
+    let reader = BufReader::new(file);
+    let reduction = if tracing {
+        reduce_reader_with_trace(reader, opposite_polarity)
+    } else {
+        reduce_reader(reader, opposite_polarity)
+    };
+    match reduction {
+        Ok(reduction) => reduction,
         Err(err) => {
-            println!(
-                "Failed to read input file '{}': {}",
-                filename,
-                err.to_string()
-            );
+            println!("Failed to read input file '{}': {}", filename, err);
             exit(3);
         }
     }
@@ -1,4 +1,9 @@
-use day05::{reduced_polymer_len, shortest_reduction};
+use day05::{
+    opposite_polarity, reduce, reduce_reader, reduce_reader_with_trace,
+    reduce_with_trace, reduced_polymer_len, shortest_reduction,
+    shortest_reduction_reader, ImpactReport, ReactionTable, UnitImpact,
+};
+use std::io::{self, Read};
 
 #[test]
 fn examples_part1() {
@@ -13,3 +18,149 @@ fn examples_part1() {
 fn examples_part2() {
     assert_eq!(shortest_reduction("dabAcCaCBAcCcaDA"), 4);
 }
+
+#[test]
+fn reduced_polymer() {
+    let reduction = reduce("dabAcCaCBAcCcaDA".chars(), opposite_polarity);
+    assert_eq!(reduction.polymer, "dabCBAcaDA");
+    assert_eq!(reduction.removed, 6);
+    assert_eq!(reduction.unit_types, vec!['a', 'b', 'c', 'd']);
+    assert_eq!(reduction.trace, None);
+}
+
+#[test]
+fn custom_reactions() {
+    let table = ReactionTable::new(vec![('α', 'Ω'), ('x', 'y')]);
+    let reduction = reduce("aΩαbyxyA".chars(), |u1, u2| table.reacts(u1, u2));
+    assert_eq!(reduction.polymer, "abyA");
+    assert_eq!(reduction.removed, 4);
+
+    let reduction = reduce("abcCBA".chars(), |u1, u2| u1 == u2);
+    assert_eq!(reduction.polymer, "abcCBA");
+    assert_eq!(reduction.removed, 0);
+}
+
+struct ByteReader<'a>(&'a [u8]);
+
+impl<'a> Read for ByteReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.0.split_first() {
+            Some((byte, rest)) if !buf.is_empty() => {
+                buf[0] = *byte;
+                self.0 = rest;
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+#[test]
+fn streaming_reduction() {
+    let polymer = "dabAcCaCBAcCcaDA";
+    let reduction = reduce_reader(polymer.as_bytes(), opposite_polarity);
+    assert_eq!(reduction.unwrap().polymer, "dabCBAcaDA");
+    assert_eq!(shortest_reduction_reader(polymer.as_bytes()).unwrap(), 4);
+
+    let table = ReactionTable::new(vec![('α', 'Ω')]);
+    let reduction =
+        reduce_reader(ByteReader("xαΩαy".as_bytes()), |u1, u2| {
+            table.reacts(u1, u2)
+        });
+    assert_eq!(reduction.unwrap().polymer, "xαy");
+
+    let truncated = &"aΩ".as_bytes()[..2];
+    assert!(reduce_reader(truncated, opposite_polarity).is_err());
+}
+
+#[test]
+fn impact_report() {
+    let reduction = reduce("dabAcCaCBAcCcaDA".chars(), opposite_polarity);
+    let report = ImpactReport::new(&reduction, opposite_polarity);
+    let impacts: Vec<(char, usize, usize)> = report
+        .units()
+        .iter()
+        .map(|impact| (impact.unit, impact.reduced_len, impact.reactions))
+        .collect();
+    assert_eq!(
+        impacts,
+        vec![('a', 6, 0), ('b', 8, 0), ('c', 4, 2), ('d', 6, 1)]
+    );
+    assert_eq!(
+        report.shortest(),
+        Some(&UnitImpact {
+            unit: 'c',
+            reduced_len: 4,
+            reactions: 2,
+        })
+    );
+    assert_eq!(report.trace(), None);
+
+    // Unit types that react away completely are still listed
+    let report = ImpactReport::new(
+        &reduce("aAb".chars(), opposite_polarity),
+        opposite_polarity,
+    );
+    assert_eq!(
+        report.units(),
+        &[
+            UnitImpact {
+                unit: 'a',
+                reduced_len: 1,
+                reactions: 0,
+            },
+            UnitImpact {
+                unit: 'b',
+                reduced_len: 0,
+                reactions: 0,
+            },
+        ]
+    );
+    let report = ImpactReport::new(
+        &reduce("aA".chars(), opposite_polarity),
+        opposite_polarity,
+    );
+    assert_eq!(report.shortest().map(|impact| impact.reduced_len), Some(0));
+
+    // Removals are reduced by the same rules as the polymer
+    let table = ReactionTable::new(vec![('x', 'X'), ('y', 'Y')]);
+    let reacts = |u1, u2| table.reacts(u1, u2);
+    let reduction = reduce("xaAXyY".chars(), reacts);
+    assert_eq!(reduction.polymer, "xaAX");
+    let report = ImpactReport::new(&reduction, reacts);
+    let impacts: Vec<(char, usize, usize)> = report
+        .units()
+        .iter()
+        .map(|impact| (impact.unit, impact.reduced_len, impact.reactions))
+        .collect();
+    assert_eq!(impacts, vec![('a', 0, 1), ('x', 2, 0), ('y', 4, 0)]);
+}
+
+#[test]
+fn reaction_trace() {
+    let polymer = "dabAcCaCBAcCcaDA";
+    let reduction = reduce_with_trace(polymer.chars(), opposite_polarity);
+    assert_eq!(reduction.polymer, "dabCBAcaDA");
+    assert_eq!(reduction.trace, Some(vec![(4, 5), (3, 6), (10, 11)]));
+
+    let reduction =
+        reduce_reader_with_trace(polymer.as_bytes(), opposite_polarity)
+            .unwrap();
+    let report = ImpactReport::new(&reduction, opposite_polarity);
+    assert_eq!(report.trace(), Some(&[(4, 5), (3, 6), (10, 11)][..]));
+}
+
+#[test]
+fn many_unit_types() {
+    let polymer: String = (0x4e00..0x4e00 + 500)
+        .map(|code| std::char::from_u32(code).unwrap())
+        .collect();
+    let reduction = reduce(polymer.chars(), opposite_polarity);
+    let report = ImpactReport::new(&reduction, opposite_polarity);
+    assert_eq!(report.units().len(), 500);
+    assert!(report
+        .units()
+        .iter()
+        .all(|impact| impact.reduced_len == 499));
+    assert_eq!(report.units()[1].unit, '\u{4e01}');
+}
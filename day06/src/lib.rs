@@ -1,9 +1,12 @@
 use regex::Regex;
-use std::collections::HashMap;
-use std::collections::HashSet;
-use std::io::{Error, ErrorKind};
+use std::cmp::{max, min};
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::io::{self, Error, ErrorKind};
 use std::str::FromStr;
 
+const MAX_GRID_CELLS: i64 = 100_000_000;
+
 #[derive(Eq, PartialEq, Hash, Debug)]
 pub struct Point {
     pub x: i32,
@@ -38,85 +41,717 @@ impl FromStr for Point {
     }
 }
 
-pub fn part1(points: &[Point]) -> i32 {
-    // Forgive me Algorithm God for I have sinned...
-    // this is a brute-force pile of ugliness...
-    let mut infinite_areas = HashSet::new();
-    let mut area_size: HashMap<&Point, i32> = HashMap::new();
-    let max_x = points.iter().map(|p| p.x).max().unwrap();
-    let max_y = points.iter().map(|p| p.y).max().unwrap();
-    let min_x = points.iter().map(|p| p.x).min().unwrap();
-    let min_y = points.iter().map(|p| p.y).min().unwrap();
-    for x in min_x..=max_x {
-        for y in &[min_y, max_y] {
-            if let Some(point) = owner_point(x, *y, points) {
-                infinite_areas.insert(point);
+pub trait Metric {
+    fn distance(&self, delta_x: i64, delta_y: i64) -> i64;
+
+    // Unit moves whose shortest paths add up to the distance, which allows
+    // owners to be found with a BFS instead of comparing every point
+    fn moves(&self) -> Option<&[(i64, i64)]> {
+        None
+    }
+
+    // How far the grid must extend past the bounding box for the owners of
+    // its outer border to be exactly the points with infinite areas
+    fn margin(&self, points: &[Point]) -> i64 {
+        Bounds::new(points, 0).span() + 1
+    }
+
+    fn infinite_areas(&self, points: &[Point], grid: &OwnerGrid) -> Vec<bool> {
+        let mut infinite_areas = vec![false; points.len()];
+        for (pos, owner) in grid.owners.iter().enumerate() {
+            if let Owner::Point(idx) = *owner {
+                if grid.is_border(pos) {
+                    infinite_areas[idx] = true;
+                }
+            }
+        }
+        infinite_areas
+    }
+
+    // Locations far enough past the bounding box are too far from all
+    // points; regions that need more than MAX_GRID_CELLS to scan are not
+    // counted
+    fn safe_region_size(&self, points: &[Point], limit: i64) -> Option<u64> {
+        let num_points = points.len() as i64;
+        let mut margin = 0;
+        Bounds::new(points, margin).cells()?;
+        while num_points.saturating_mul(self.distance(margin, 0)) < limit {
+            margin += 1;
+            Bounds::new(points, margin).cells()?;
+        }
+
+        let bounds = Bounds::new(points, margin);
+        let mut count: u64 = 0;
+        for y_pos in bounds.min_y..=bounds.max_y {
+            for x_pos in bounds.min_x..=bounds.max_x {
+                let mut total = 0;
+                for point in points {
+                    total += self.distance(
+                        x_pos - i64::from(point.x),
+                        y_pos - i64::from(point.y),
+                    );
+                    if total >= limit {
+                        break;
+                    }
+                }
+                if total < limit {
+                    count = count.checked_add(1)?;
+                }
+            }
+        }
+        Some(count)
+    }
+}
+
+pub struct Manhattan;
+
+pub struct Chebyshev;
+
+pub struct SquaredEuclidean;
+
+impl Metric for Manhattan {
+    fn distance(&self, delta_x: i64, delta_y: i64) -> i64 {
+        delta_x.abs() + delta_y.abs()
+    }
+
+    fn moves(&self) -> Option<&[(i64, i64)]> {
+        Some(&[(-1, 0), (1, 0), (0, -1), (0, 1)])
+    }
+
+    // Moving away from the bounding box increases the distance to all
+    // points equally, so owners of its border have infinite areas
+    fn margin(&self, _points: &[Point]) -> i64 {
+        0
+    }
+
+    fn safe_region_size(&self, points: &[Point], limit: i64) -> Option<u64> {
+        let x_distances = AxisDistances::new(points.iter().map(|p| p.x));
+        let y_distances = AxisDistances::new(points.iter().map(|p| p.y));
+        let (first_x, last_x) = match x_distances.range_below(limit) {
+            Some(range) => range,
+            None => return Some(0),
+        };
+
+        let mut count: u64 = 0;
+        for x_pos in first_x..=last_x {
+            let y_limit = limit - x_distances.total(x_pos);
+            if let Some((first_y, last_y)) = y_distances.range_below(y_limit) {
+                count = count.checked_add((last_y - first_y + 1) as u64)?;
             }
         }
+        Some(count)
     }
-    for y in min_y..=max_y {
-        for x in &[min_x, max_x] {
-            if let Some(point) = owner_point(*x, y, points) {
-                infinite_areas.insert(point);
+}
+
+impl Metric for Chebyshev {
+    fn distance(&self, delta_x: i64, delta_y: i64) -> i64 {
+        max(delta_x.abs(), delta_y.abs())
+    }
+
+    fn moves(&self) -> Option<&[(i64, i64)]> {
+        Some(&[
+            (-1, -1),
+            (0, -1),
+            (1, -1),
+            (-1, 0),
+            (1, 0),
+            (-1, 1),
+            (0, 1),
+            (1, 1),
+        ])
+    }
+}
+
+impl Metric for SquaredEuclidean {
+    fn distance(&self, delta_x: i64, delta_y: i64) -> i64 {
+        delta_x * delta_x + delta_y * delta_y
+    }
+
+    // Finite areas can stretch far past the bounding box, so the margin
+    // comes from the furthest vertex of any finite Voronoi cell
+    fn margin(&self, points: &[Point]) -> i64 {
+        let bounds = Bounds::new(points, 0);
+        let on_hull = on_convex_hull(points);
+        let mut margin = 0.0_f64;
+
+        for (point, _) in points.iter().zip(on_hull).filter(|(_, on)| !on) {
+            for (vertex_x, vertex_y) in voronoi_cell(point, points, &bounds) {
+                margin = margin
+                    .max(bounds.min_x as f64 - vertex_x)
+                    .max(vertex_x - bounds.max_x as f64)
+                    .max(bounds.min_y as f64 - vertex_y)
+                    .max(vertex_y - bounds.max_y as f64);
             }
         }
+        // Margins this large never fit in a grid, but must not overflow
+        let margin = margin.ceil().min(f64::from(i32::MAX)) as i64;
+        max(margin + 1, bounds.span() + 1)
+    }
+
+    // Only points on the convex hull are closest to some location in every
+    // direction far enough away
+    fn infinite_areas(&self, points: &[Point], _grid: &OwnerGrid) -> Vec<bool> {
+        on_convex_hull(points)
     }
-    for x_pos in (min_x + 1)..max_x {
-        for y_pos in (min_y + 1)..max_y {
-            if let Some(point) = owner_point(x_pos, y_pos, points) {
-                if !infinite_areas.contains(point) {
-                    area_size
-                        .entry(point)
-                        .and_modify(|count| *count += 1)
-                        .or_insert(1);
+}
+
+// Vertices of the region closer to the point than to any other point,
+// clipped to a square large enough to hold every bounded region
+fn voronoi_cell(
+    point: &Point,
+    points: &[Point],
+    bounds: &Bounds,
+) -> Vec<(f64, f64)> {
+    // Voronoi vertices of integer points are within a cubic factor of the span
+    let size = 8.0 * (bounds.span() as f64 + 1.0).powi(3);
+    let (center_x, center_y) = (point.x as f64, point.y as f64);
+    let mut cell = vec![
+        (center_x - size, center_y - size),
+        (center_x + size, center_y - size),
+        (center_x + size, center_y + size),
+        (center_x - size, center_y + size),
+    ];
+
+    for other in points.iter().filter(|other| *other != point) {
+        // Keep the half-plane of locations (x, y) with a.x + b.y <= c
+        let (a, b) = ((other.x - point.x) as f64, (other.y - point.y) as f64);
+        let c = a * (point.x + other.x) as f64 / 2.0
+            + b * (point.y + other.y) as f64 / 2.0;
+        let inside = |(x, y): (f64, f64)| a * x + b * y <= c;
+
+        let mut clipped = Vec::with_capacity(cell.len() + 1);
+        for (idx, &from) in cell.iter().enumerate() {
+            let to = cell[(idx + 1) % cell.len()];
+            if inside(from) {
+                clipped.push(from);
+            }
+            if inside(from) != inside(to) {
+                let (from_val, to_val) =
+                    (a * from.0 + b * from.1 - c, a * to.0 + b * to.1 - c);
+                let ratio = from_val / (from_val - to_val);
+                clipped.push((
+                    from.0 + ratio * (to.0 - from.0),
+                    from.1 + ratio * (to.1 - from.1),
+                ));
+            }
+        }
+        cell = clipped;
+    }
+    cell
+}
+
+fn on_convex_hull(points: &[Point]) -> Vec<bool> {
+    let hull = convex_hull(points);
+    points
+        .iter()
+        .map(|point| {
+            hull.len() < 3
+                || hull
+                    .iter()
+                    .zip(hull.iter().cycle().skip(1))
+                    .any(|(from, to)| on_segment(point, from, to))
+        })
+        .collect()
+}
+
+fn cross(origin: &Point, point1: &Point, point2: &Point) -> i64 {
+    i64::from(point1.x - origin.x) * i64::from(point2.y - origin.y)
+        - i64::from(point1.y - origin.y) * i64::from(point2.x - origin.x)
+}
+
+fn on_segment(point: &Point, from: &Point, to: &Point) -> bool {
+    cross(from, to, point) == 0
+        && min(from.x, to.x) <= point.x
+        && point.x <= max(from.x, to.x)
+        && min(from.y, to.y) <= point.y
+        && point.y <= max(from.y, to.y)
+}
+
+// Vertices of the convex hull in counter-clockwise order (monotone chain)
+fn convex_hull(points: &[Point]) -> Vec<&Point> {
+    let mut sorted: Vec<&Point> = points.iter().collect();
+    sorted.sort_unstable_by_key(|point| (point.x, point.y));
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    let mut lower = half_hull(sorted.iter().cloned());
+    let mut upper = half_hull(sorted.iter().rev().cloned());
+    // The last point of each half is the first point of the other
+    lower.pop();
+    upper.pop();
+    lower.append(&mut upper);
+    lower
+}
+
+fn half_hull<'a, I: Iterator<Item = &'a Point>>(points: I) -> Vec<&'a Point> {
+    let mut hull: Vec<&Point> = Vec::new();
+    for point in points {
+        while hull.len() >= 2
+            && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0
+        {
+            hull.pop();
+        }
+        hull.push(point);
+    }
+    hull
+}
+
+struct Bounds {
+    min_x: i64,
+    max_x: i64,
+    min_y: i64,
+    max_y: i64,
+}
+
+impl Bounds {
+    fn new(points: &[Point], margin: i64) -> Self {
+        Bounds {
+            min_x: i64::from(points.iter().map(|p| p.x).min().unwrap())
+                - margin,
+            max_x: i64::from(points.iter().map(|p| p.x).max().unwrap())
+                + margin,
+            min_y: i64::from(points.iter().map(|p| p.y).min().unwrap())
+                - margin,
+            max_y: i64::from(points.iter().map(|p| p.y).max().unwrap())
+                + margin,
+        }
+    }
+
+    fn span(&self) -> i64 {
+        max(self.max_x - self.min_x, self.max_y - self.min_y)
+    }
+
+    // Number of locations within bounds, if small enough to scan
+    fn cells(&self) -> Option<i64> {
+        (self.max_x - self.min_x + 1)
+            .checked_mul(self.max_y - self.min_y + 1)
+            .filter(|&cells| cells <= MAX_GRID_CELLS)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Owner {
+    Unreached,
+    Point(usize),
+    Tie,
+}
+
+// Closest point to every location in the bounding box of all points,
+// extended by the margin required by the metric
+pub struct OwnerGrid {
+    min_x: i64,
+    min_y: i64,
+    width: usize,
+    height: usize,
+    owners: Vec<Owner>,
+    infinite_areas: Vec<bool>,
+}
+
+impl OwnerGrid {
+    pub fn new<M: Metric>(points: &[Point], metric: &M) -> Result<Self, Error> {
+        OwnerGrid::with_margin(points, metric, metric.margin(points))
+    }
+
+    fn with_margin<M: Metric>(
+        points: &[Point],
+        metric: &M,
+        margin: i64,
+    ) -> Result<Self, Error> {
+        let bounds = Bounds::new(points, margin);
+        if bounds.cells().is_none() {
+            return Err(Error::new(ErrorKind::InvalidInput, "Grid too large"));
+        }
+        let width = (bounds.max_x - bounds.min_x + 1) as usize;
+        let height = (bounds.max_y - bounds.min_y + 1) as usize;
+
+        let mut grid = OwnerGrid {
+            min_x: bounds.min_x,
+            min_y: bounds.min_y,
+            width,
+            height,
+            owners: vec![Owner::Unreached; width * height],
+            infinite_areas: Vec::new(),
+        };
+        match metric.moves() {
+            Some(moves) => grid.find_owners_bfs(points, moves),
+            None => grid.find_owners(points, metric),
+        }
+        grid.infinite_areas = metric.infinite_areas(points, &grid);
+        Ok(grid)
+    }
+
+    fn find_owners<M: Metric>(&mut self, points: &[Point], metric: &M) {
+        for (pos, owner) in self.owners.iter_mut().enumerate() {
+            let x_pos = self.min_x + (pos % self.width) as i64;
+            let y_pos = self.min_y + (pos / self.width) as i64;
+            let mut min_dist = None;
+            for (idx, point) in points.iter().enumerate() {
+                let dist = metric.distance(
+                    x_pos - i64::from(point.x),
+                    y_pos - i64::from(point.y),
+                );
+                if min_dist.is_none_or(|min_dist| dist < min_dist) {
+                    *owner = Owner::Point(idx);
+                    min_dist = Some(dist);
+                } else if min_dist == Some(dist) {
+                    // Position may belong to multiple points
+                    *owner = Owner::Tie;
                 }
             }
         }
     }
-    *area_size.values().max().unwrap_or(&0)
+
+    fn find_owners_bfs(&mut self, points: &[Point], moves: &[(i64, i64)]) {
+        let mut distances = vec![u32::MAX; self.owners.len()];
+        let mut queue = VecDeque::new();
+
+        for (idx, point) in points.iter().enumerate() {
+            let pos = self
+                .position(i64::from(point.x), i64::from(point.y))
+                .unwrap();
+            if self.owners[pos] == Owner::Unreached {
+                self.owners[pos] = Owner::Point(idx);
+                distances[pos] = 0;
+                queue.push_back(pos);
+            }
+        }
+
+        // Multi-source BFS: a location is owned by a single point only if
+        // all its neighbours on a shortest path are owned by that point
+        while let Some(pos) = queue.pop_front() {
+            let x_pos = self.min_x + (pos % self.width) as i64;
+            let y_pos = self.min_y + (pos / self.width) as i64;
+            let next_dist = distances[pos] + 1;
+            for (move_x, move_y) in moves {
+                let next = match self.position(x_pos + move_x, y_pos + move_y) {
+                    Some(next) => next,
+                    None => continue,
+                };
+                if distances[next] == u32::MAX {
+                    distances[next] = next_dist;
+                    self.owners[next] = self.owners[pos];
+                    queue.push_back(next);
+                } else if distances[next] == next_dist
+                    && self.owners[next] != self.owners[pos]
+                {
+                    self.owners[next] = Owner::Tie;
+                }
+            }
+        }
+    }
+
+    fn position(&self, x_pos: i64, y_pos: i64) -> Option<usize> {
+        let (x_off, y_off) = (x_pos - self.min_x, y_pos - self.min_y);
+        if x_off < 0
+            || y_off < 0
+            || x_off >= self.width as i64
+            || y_off >= self.height as i64
+        {
+            None
+        } else {
+            Some(y_off as usize * self.width + x_off as usize)
+        }
+    }
+
+    fn is_border(&self, pos: usize) -> bool {
+        let (x, y) = (pos % self.width, pos / self.width);
+        x == 0 || y == 0 || x + 1 == self.width || y + 1 == self.height
+    }
 }
 
-fn owner_point(x_pos: i32, y_pos: i32, points: &[Point]) -> Option<&Point> {
-    let mut nearest = None;
-    let mut min_dist = None;
-    for point in points.iter() {
-        let dist = manhattan_distance(x_pos, y_pos, point);
-        if dist == 0 {
-            return Some(point);
+const TIE_COLOR: [u8; 3] = [255, 255, 255];
+const COORDINATE_COLOR: [u8; 3] = [0, 0, 0];
+const SAFE_COLOR: [u8; 3] = [255, 255, 0];
+
+// Map of the areas owned by each point under the Manhattan distance, as
+// computed for part 1, optionally highlighting the part 2 safe region
+pub struct VoronoiMap<'a> {
+    points: &'a [Point],
+    grid: OwnerGrid,
+    safe_region: Vec<bool>,
+}
+
+impl<'a> VoronoiMap<'a> {
+    pub fn new(
+        points: &'a [Point],
+        max_total_dist: Option<u32>,
+    ) -> Result<Self, Error> {
+        if points.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "No points given"));
+        }
+
+        let x_distances = AxisDistances::new(points.iter().map(|p| p.x));
+        let y_distances = AxisDistances::new(points.iter().map(|p| p.y));
+        let limit = max_total_dist.map_or(0, i64::from);
+
+        // Leave room around the points, and for the whole safe region
+        let bounds = Bounds::new(points, 0);
+        let mut margin = 1;
+        let min_x_total = x_distances.total(x_distances.median());
+        let min_y_total = y_distances.total(y_distances.median());
+        if let Some((first_x, last_x)) =
+            x_distances.range_below(limit - min_y_total)
+        {
+            margin =
+                max(margin, max(bounds.min_x - first_x, last_x - bounds.max_x));
+        }
+        if let Some((first_y, last_y)) =
+            y_distances.range_below(limit - min_x_total)
+        {
+            margin =
+                max(margin, max(bounds.min_y - first_y, last_y - bounds.max_y));
+        }
+
+        let grid = OwnerGrid::with_margin(points, &Manhattan, margin)?;
+        let x_totals: Vec<i64> = (0..grid.width as i64)
+            .map(|x_off| x_distances.total(grid.min_x + x_off))
+            .collect();
+        let y_totals: Vec<i64> = (0..grid.height as i64)
+            .map(|y_off| y_distances.total(grid.min_y + y_off))
+            .collect();
+        let safe_region = (0..grid.owners.len())
+            .map(|pos| {
+                x_totals[pos % grid.width] + y_totals[pos / grid.width] < limit
+            })
+            .collect();
+
+        Ok(VoronoiMap {
+            points,
+            grid,
+            safe_region,
+        })
+    }
+
+    fn coordinates(&self) -> Vec<Option<usize>> {
+        let mut coordinates = vec![None; self.grid.owners.len()];
+        for (idx, point) in self.points.iter().enumerate() {
+            let pos = self
+                .grid
+                .position(i64::from(point.x), i64::from(point.y))
+                .unwrap();
+            coordinates[pos] = Some(idx);
+        }
+        coordinates
+    }
+
+    // Puzzle-style map: lowercase letters for the area of each point,
+    // uppercase for the points themselves, '.' for ties and '#' for the
+    // safe region; letters repeat when there are more than 26 points
+    pub fn to_ascii(&self) -> String {
+        let coordinates = self.coordinates();
+        let mut ascii = String::new();
+        for (pos, owner) in self.grid.owners.iter().enumerate() {
+            let symbol = match (coordinates[pos], *owner) {
+                (Some(idx), _) => label(idx).to_ascii_uppercase(),
+                _ if self.safe_region[pos] => '#',
+                (None, Owner::Point(idx)) => label(idx),
+                (None, _) => '.',
+            };
+            ascii.push(symbol);
+            if (pos + 1) % self.grid.width == 0 {
+                ascii.push('\n');
+            }
+        }
+        ascii
+    }
+
+    // Area of each point, or whether it is infinite
+    pub fn legend(&self) -> String {
+        let mut area_size = vec![0; self.points.len()];
+        for owner in self.grid.owners.iter() {
+            if let Owner::Point(idx) = *owner {
+                area_size[idx] += 1;
+            }
+        }
+
+        let mut legend = String::new();
+        for (idx, point) in self.points.iter().enumerate() {
+            write!(
+                legend,
+                "{} ({}, {}): ",
+                label(idx).to_ascii_uppercase(),
+                point.x,
+                point.y
+            )
+            .unwrap();
+            if self.grid.infinite_areas[idx] {
+                legend.push_str("infinite\n");
+            } else {
+                writeln!(legend, "area {}", area_size[idx]).unwrap();
+            }
         }
-        if min_dist.map_or(true, |min_dist| dist < min_dist) {
-            nearest = Some(point);
-            min_dist = Some(dist);
-        } else if min_dist.map_or(false, |min_dist| dist == min_dist) {
-            // Position may belong to multiple points
-            nearest = None;
+        legend
+    }
+
+    // Each area gets its own color, darkened if it is infinite, and the
+    // safe region is tinted yellow
+    pub fn render_ppm<W: io::Write>(&self, out: &mut W) -> io::Result<()> {
+        let coordinates = self.coordinates();
+        let mut pixels = Vec::with_capacity(3 * self.grid.owners.len());
+        for (pos, owner) in self.grid.owners.iter().enumerate() {
+            let mut color = match (coordinates[pos], *owner) {
+                (Some(_), _) => COORDINATE_COLOR,
+                (None, Owner::Point(idx)) => {
+                    area_color(idx, self.grid.infinite_areas[idx])
+                }
+                (None, _) => TIE_COLOR,
+            };
+            if self.safe_region[pos] && coordinates[pos].is_none() {
+                for (channel, safe) in color.iter_mut().zip(SAFE_COLOR.iter()) {
+                    *channel =
+                        ((u16::from(*channel) + u16::from(*safe)) / 2) as u8;
+                }
+            }
+            pixels.extend_from_slice(&color);
         }
+
+        write!(out, "P6\n{} {}\n255\n", self.grid.width, self.grid.height)?;
+        out.write_all(&pixels)
     }
-    nearest
 }
 
-fn manhattan_distance(x_pos: i32, y_pos: i32, point: &Point) -> i32 {
-    (point.x - x_pos).abs() + (point.y - y_pos).abs()
+fn label(idx: usize) -> char {
+    (b'a' + (idx % 26) as u8) as char
+}
+
+// Spread hues by the golden angle so that neighbouring indices differ
+fn area_color(idx: usize, infinite: bool) -> [u8; 3] {
+    let hue = (idx as f64 * 0.618_033_988_75).fract() * 6.0;
+    let value = if infinite { 0.45 } else { 0.95 };
+    let (saturation, sector) = (0.6, hue.floor());
+    let fract = hue - sector;
+    let (low, falling, rising) = (
+        value * (1.0 - saturation),
+        value * (1.0 - saturation * fract),
+        value * (1.0 - saturation * (1.0 - fract)),
+    );
+    let (red, green, blue) = match sector as u8 {
+        0 => (value, rising, low),
+        1 => (falling, value, low),
+        2 => (low, value, rising),
+        3 => (low, falling, value),
+        4 => (rising, low, value),
+        _ => (value, low, falling),
+    };
+    [
+        (red * 255.0).round() as u8,
+        (green * 255.0).round() as u8,
+        (blue * 255.0).round() as u8,
+    ]
 }
 
-pub fn part2(points: &[Point], _max_tot_dist: i32) -> i32 {
-    // More brute-force uglinesses
-    let max_x = points.iter().map(|p| p.x).max().unwrap();
-    let max_y = points.iter().map(|p| p.y).max().unwrap();
-    let min_x = points.iter().map(|p| p.x).min().unwrap();
-    let min_y = points.iter().map(|p| p.y).min().unwrap();
-    let mut count = 0;
-    for x_pos in min_x..=max_x {
-        for y_pos in min_y..=max_y {
-            if points
-                .iter()
-                .map(|point| manhattan_distance(x_pos, y_pos, point))
-                .sum::<i32>()
-                < _max_tot_dist
-            {
-                count += 1
+pub fn largest_finite_area<M: Metric>(
+    points: &[Point],
+    metric: &M,
+) -> Result<usize, Error> {
+    if points.is_empty() {
+        return Ok(0);
+    }
+
+    let grid = OwnerGrid::new(points, metric)?;
+    let mut area_size = vec![0; points.len()];
+    for owner in grid.owners.iter() {
+        if let Owner::Point(idx) = *owner {
+            area_size[idx] += 1;
+        }
+    }
+
+    Ok(area_size
+        .into_iter()
+        .zip(grid.infinite_areas.iter())
+        .filter(|(_, &infinite)| !infinite)
+        .map(|(size, _)| size)
+        .max()
+        .unwrap_or(0))
+}
+
+pub fn safe_region_size<M: Metric>(
+    points: &[Point],
+    metric: &M,
+    max_total_dist: u32,
+) -> Result<u64, Error> {
+    if points.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidInput, "No points given"));
+    }
+
+    // The region may extend past the bounding box of the points, as far
+    // as the maximum total distance allows
+    metric
+        .safe_region_size(points, i64::from(max_total_dist))
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Region too large"))
+}
+
+pub fn part1(points: &[Point]) -> Result<usize, Error> {
+    largest_finite_area(points, &Manhattan)
+}
+
+// Sum of distances along one axis, evaluated from sorted prefix sums
+struct AxisDistances {
+    coords: Vec<i64>,
+    prefix_sums: Vec<i64>,
+}
+
+impl AxisDistances {
+    fn new<I: Iterator<Item = i32>>(coords: I) -> Self {
+        let mut coords: Vec<i64> = coords.map(i64::from).collect();
+        coords.sort_unstable();
+        let mut prefix_sums = vec![0];
+        for coord in coords.iter() {
+            prefix_sums.push(prefix_sums.last().unwrap() + coord);
+        }
+        AxisDistances {
+            coords,
+            prefix_sums,
+        }
+    }
+
+    fn total(&self, pos: i64) -> i64 {
+        let num_before = self.coords.partition_point(|&coord| coord < pos);
+        let num_after = self.coords.len() - num_before;
+        let sum_before = self.prefix_sums[num_before];
+        let sum_after = self.prefix_sums[self.coords.len()] - sum_before;
+        (pos * num_before as i64 - sum_before)
+            + (sum_after - pos * num_after as i64)
+    }
+
+    // The total distance is convex and minimal at the median, so positions
+    // with a total below the limit form a single range around it
+    fn median(&self) -> i64 {
+        self.coords[self.coords.len() / 2]
+    }
+
+    fn range_below(&self, limit: i64) -> Option<(i64, i64)> {
+        let median = self.median();
+        if self.total(median) >= limit {
+            return None;
+        }
+
+        let (mut low, mut high) = (median - limit, median);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.total(mid) < limit {
+                high = mid;
+            } else {
+                low = mid + 1;
             }
         }
+        let first = low;
+
+        let (mut low, mut high) = (median, median + limit);
+        while low < high {
+            let mid = low + (high - low + 1) / 2;
+            if self.total(mid) < limit {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        Some((first, low))
     }
-    count
+}
+
+pub fn part2(points: &[Point], max_total_dist: u32) -> Result<u64, Error> {
+    safe_region_size(points, &Manhattan, max_total_dist)
 }
@@ -1,9 +1,11 @@
 use clap::{crate_description, App, Arg};
-use day06::{part1, part2, Point};
+use day06::{part1, part2, Point, VoronoiMap};
 use std::fs::File;
-use std::io::{BufRead, BufReader, Error, ErrorKind};
+use std::io::{BufRead, BufReader, BufWriter, Error, ErrorKind};
 use std::process::exit;
 
+const MAX_TOTAL_DIST: u32 = 10_000;
+
 fn main() {
     let args = App::new(crate_description!())
         .arg(
@@ -12,19 +14,67 @@ fn main() {
                 .required(true)
                 .index(1),
         )
+        .arg(
+            Arg::with_name("map")
+                .long("map")
+                .help("Prints a map of the areas and the safe region"),
+        )
+        .arg(
+            Arg::with_name("render")
+                .long("render")
+                .value_name("FILE")
+                .help("Renders the areas and the safe region as a PPM image"),
+        )
         .get_matches();
 
     println!(crate_description!());
     let points = read_input(args.value_of("INPUT").unwrap());
-    println!("Part 1: {}", part1(&points));
-    println!("Part 2: {}", part2(&points, 10_000));
+    match part1(&points) {
+        Ok(size) => println!("Part 1: {}", size),
+        Err(err) => {
+            println!("Invalid input: {}", err);
+            exit(4);
+        }
+    }
+    match part2(&points, MAX_TOTAL_DIST) {
+        Ok(size) => println!("Part 2: {}", size),
+        Err(err) => {
+            println!("Invalid input: {}", err);
+            exit(4);
+        }
+    }
+
+    if args.is_present("map") || args.is_present("render") {
+        let map = match VoronoiMap::new(&points, Some(MAX_TOTAL_DIST)) {
+            Ok(map) => map,
+            Err(err) => {
+                println!("Failed to build map: {}", err);
+                exit(4);
+            }
+        };
+        if args.is_present("map") {
+            print!("{}{}", map.to_ascii(), map.legend());
+        }
+        if let Some(filename) = args.value_of("render") {
+            render_output(filename, &map);
+        }
+    }
+}
+
+fn render_output(filename: &str, map: &VoronoiMap) {
+    let result = File::create(filename)
+        .and_then(|file| map.render_ppm(&mut BufWriter::new(file)));
+    if let Err(err) = result {
+        println!("Failed to render image '{}': {}", filename, err);
+        exit(5);
+    }
 }
 
 fn read_input(filename: &str) -> Vec<Point> {
     let file = match File::open(filename) {
         Ok(file) => file,
         Err(err) => {
-            println!("Failed to open file '{}': {}", filename, err.to_string());
+            println!("Failed to open file '{}': {}", filename, err);
             exit(2);
         }
     };
@@ -42,11 +92,7 @@ fn read_input(filename: &str) -> Vec<Point> {
     {
         Ok(input) => input,
         Err(err) => {
-            println!(
-                "Failed to parse input file '{}': {}",
-                filename,
-                err.to_string()
-            );
+            println!("Failed to parse input file '{}': {}", filename, err);
             exit(3);
         }
     }
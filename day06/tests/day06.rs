@@ -1,4 +1,7 @@
-use day06::{part1, part2, Point};
+use day06::{
+    largest_finite_area, part1, part2, safe_region_size, Chebyshev, Manhattan,
+    Point, SquaredEuclidean, VoronoiMap,
+};
 
 fn parse_input(input: &str) -> Vec<Point> {
     input.lines().map(|s| s.trim().parse().unwrap()).collect()
@@ -13,7 +16,167 @@ fn examples() {
                  5, 5
                  8, 9";
 
-    let points = parse_input(&input);
-    assert_eq!(part1(&points), 17);
-    assert_eq!(part2(&points, 32), 16);
+    let points = parse_input(input);
+    assert_eq!(part1(&points).unwrap(), 17);
+    assert_eq!(part2(&points, 32).unwrap(), 16);
+}
+
+#[test]
+fn many_points() {
+    let points: Vec<Point> = (0..100)
+        .flat_map(|i| {
+            (0..100).map(move |j| Point {
+                x: 10 * i,
+                y: 10 * j,
+            })
+        })
+        .collect();
+    assert_eq!(part1(&points).unwrap(), 81);
+}
+
+#[test]
+fn part2_matches_brute_force() {
+    let input = "3, 14
+                 7, 2
+                 15, 9
+                 1, 1
+                 11, 11";
+    let points = parse_input(input);
+    for &max_tot_dist in [0, 20, 40, 55, 70, 100].iter() {
+        let mut expected = 0;
+        for x in -30..=45 {
+            for y in -30..=45 {
+                let total: u32 = points
+                    .iter()
+                    .map(|p| ((p.x - x).abs() + (p.y - y).abs()) as u32)
+                    .sum();
+                if total < max_tot_dist {
+                    expected += 1;
+                }
+            }
+        }
+        assert_eq!(part2(&points, max_tot_dist).unwrap(), expected);
+    }
+}
+
+#[test]
+fn part2_region_outside_bounding_box() {
+    let points = vec![Point { x: 5, y: 5 }];
+    assert_eq!(part2(&points, 0).unwrap(), 0);
+    assert_eq!(part2(&points, 1).unwrap(), 1);
+    assert_eq!(part2(&points, 3).unwrap(), 13);
+
+    let points = vec![Point { x: 0, y: 0 }, Point { x: 2, y: 0 }];
+    assert_eq!(part2(&points, 6).unwrap(), 11);
+}
+
+#[test]
+fn part2_no_points() {
+    assert!(part2(&[], 10).is_err());
+}
+
+#[test]
+fn other_metrics() {
+    let input = "1, 1
+                 1, 6
+                 8, 3
+                 3, 4
+                 5, 5
+                 8, 9";
+
+    let points = parse_input(input);
+    assert_eq!(largest_finite_area(&points, &Manhattan).unwrap(), 17);
+    assert_eq!(largest_finite_area(&points, &Chebyshev).unwrap(), 10);
+    assert_eq!(largest_finite_area(&points, &SquaredEuclidean).unwrap(), 16);
+    assert_eq!(safe_region_size(&points, &Manhattan, 32).unwrap(), 16);
+    assert_eq!(safe_region_size(&points, &Chebyshev, 32).unwrap(), 80);
+    assert_eq!(
+        safe_region_size(&points, &SquaredEuclidean, 300).unwrap(),
+        112
+    );
+}
+
+#[test]
+fn diagonal_points() {
+    let input = "0, 0
+                 5, 5
+                 10, 10";
+
+    let points = parse_input(input);
+    // Ties enclose the middle area only under the Manhattan distance
+    assert_eq!(largest_finite_area(&points, &Manhattan).unwrap(), 61);
+    assert_eq!(largest_finite_area(&points, &Chebyshev).unwrap(), 0);
+    assert_eq!(largest_finite_area(&points, &SquaredEuclidean).unwrap(), 0);
+}
+
+#[test]
+fn voronoi_map() {
+    let input = "1, 1
+                 1, 6
+                 8, 3
+                 3, 4
+                 5, 5
+                 8, 9";
+
+    let points = parse_input(input);
+    let map = VoronoiMap::new(&points, None).unwrap();
+    assert_eq!(
+        map.to_ascii(),
+        "aaaaa.cccc
+aAaaa.cccc
+aaaddecccc
+aadddeccCc
+..dDdeeccc
+bb.deEeecc
+bBb.eeee..
+bbb.eeefff
+bbb.eeffff
+bbb.ffffFf
+bbb.ffffff
+"
+    );
+    assert_eq!(
+        map.legend(),
+        "A (1, 1): infinite
+B (1, 6): infinite
+C (8, 3): infinite
+D (3, 4): area 9
+E (5, 5): area 17
+F (8, 9): infinite
+"
+    );
+
+    let map = VoronoiMap::new(&points, Some(32)).unwrap();
+    assert_eq!(
+        map.to_ascii(),
+        "aaaaa.cccc
+aAaaa.cccc
+aaaddecccc
+aad###ccCc
+..#D###ccc
+bb###E#ecc
+bBb###ee..
+bbb.eeefff
+bbb.eeffff
+bbb.ffffFf
+bbb.ffffff
+"
+    );
+}
+
+#[test]
+fn grid_too_large() {
+    let input = "1000, 1000
+                 2000, 999
+                 3000, 1000
+                 2000, 0";
+
+    let points = parse_input(input);
+    assert_eq!(largest_finite_area(&points, &Manhattan).unwrap(), 0);
+    assert!(largest_finite_area(&points, &SquaredEuclidean).is_err());
+
+    let points = [Point { x: 0, y: 0 }];
+    assert!(safe_region_size(&points, &Chebyshev, u32::MAX).is_err());
+    assert!(safe_region_size(&points, &SquaredEuclidean, u32::MAX).is_err());
+    assert_eq!(safe_region_size(&points, &Chebyshev, 3).unwrap(), 25);
 }
@@ -1,46 +1,304 @@
 use regex::Regex;
+use std::cmp::{max, Reverse};
 use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::{self, Write};
+use std::rc::Rc;
 use std::str::FromStr;
 
-type Step = char;
+pub type Step = String;
 
-fn step_time(step: Step) -> u32 {
-    if step as u32 >= 'A' as u32 {
-        1 + step as u32 - 'A' as u32
+// Each letter takes as long as its position in the alphabet
+pub fn letter_duration(step: &str) -> Time {
+    step.chars()
+        .filter(|letter| letter.is_ascii_uppercase())
+        .map(|letter| 1 + letter as u32 - 'A' as u32)
+        .sum()
+}
+
+// Steps named by a single character are written without a separator, as
+// in the puzzle; longer names are separated by commas
+pub fn format_steps<'a, I>(steps: I) -> String
+where
+    I: Iterator<Item = &'a Step> + Clone,
+{
+    if steps.clone().all(|step| step.chars().count() == 1) {
+        steps.map(String::as_str).collect()
     } else {
-        0
+        steps.map(String::as_str).collect::<Vec<_>>().join(",")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StepError {
+    UnknownStep(Step),
+    Cycle(Vec<Step>),
+    NoCapableWorker(Step),
+    UnknownWorker(usize),
+}
+
+impl fmt::Display for StepError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StepError::UnknownStep(step) => {
+                write!(f, "Step {} is not one of the steps", step)
+            }
+            StepError::Cycle(steps) => {
+                write!(f, "Steps form a cycle: {}", steps.join(" -> "))
+            }
+            StepError::NoCapableWorker(step) => {
+                write!(f, "No worker can do step {}", step)
+            }
+            StepError::UnknownWorker(worker) => {
+                write!(f, "Worker {} does not exist", worker)
+            }
+        }
     }
 }
 
+impl std::error::Error for StepError {}
+
+// Details added to each step when exporting the dependency graph
+#[derive(Default)]
+pub struct GraphAnnotations<'a> {
+    pub durations: Option<&'a dyn Fn(&str) -> Time>,
+    pub serial_order: bool,
+}
+
 #[derive(Clone)]
 pub struct Steps {
     pending: HashMap<Step, HashSet<Step>>,
 }
 
 impl Steps {
-    pub fn get_serial_order(&mut self) -> String {
+    pub fn get_serial_order(&mut self) -> Result<Vec<Step>, StepError> {
+        self.check()?;
+        Ok(self.complete_available_steps())
+    }
+
+    // Longest chain of steps by total duration, which bounds the completion
+    // time no matter how many workers there are
+    pub fn critical_path<F>(
+        &self,
+        durations: F,
+    ) -> Result<(Vec<Step>, Time), StepError>
+    where
+        F: Fn(&str) -> Time,
+    {
+        let order = self.clone().get_serial_order()?;
+        let mut finish: HashMap<&Step, (Time, Option<&Step>)> = HashMap::new();
+        for step in order.iter() {
+            let blocker = self.pending[step]
+                .iter()
+                .max_by_key(|blocker| (finish[blocker].0, Reverse(*blocker)));
+            let start = blocker.map_or(0, |blocker| finish[blocker].0);
+            finish.insert(step, (start + durations(step), blocker));
+        }
+
+        let mut step = order
+            .iter()
+            .max_by_key(|step| (finish[step].0, Reverse(*step)));
+        let length = step.map_or(0, |step| finish[step].0);
+        let mut path = Vec::new();
+        while let Some(curr) = step {
+            path.push(curr.clone());
+            step = finish[curr].1;
+        }
+        path.reverse();
+        Ok((path, length))
+    }
+
+    pub fn names(&self) -> Vec<&Step> {
+        let mut names: Vec<&Step> = self.pending.keys().collect();
+        names.sort_unstable();
+        names
+    }
+
+    // Pairs of steps where the first must be finished before the second
+    pub fn dependencies(&self) -> Vec<(&Step, &Step)> {
+        let mut dependencies: Vec<(&Step, &Step)> = self
+            .pending
+            .iter()
+            .flat_map(|(step, blockers)| {
+                blockers.iter().map(move |blocker| (blocker, step))
+            })
+            .collect();
+        dependencies.sort_unstable();
+        dependencies
+    }
+
+    pub fn to_dot(&self, annotations: &GraphAnnotations) -> String {
+        let mut dot = String::from("digraph steps {\n");
+        for (step, details) in self.graph_nodes(annotations) {
+            let label = std::iter::once(step.as_str())
+                .chain(details.iter().map(String::as_str))
+                .map(dot_escape)
+                .collect::<Vec<_>>()
+                .join("\\n");
+            writeln!(dot, "  \"{}\" [label=\"{}\"];", dot_escape(step), label)
+                .unwrap();
+        }
+        for (blocker, step) in self.dependencies() {
+            writeln!(
+                dot,
+                "  \"{}\" -> \"{}\";",
+                dot_escape(blocker),
+                dot_escape(step)
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    // Mermaid node IDs are numbered, since step names may contain
+    // characters that Mermaid does not accept in IDs
+    pub fn to_mermaid(&self, annotations: &GraphAnnotations) -> String {
+        let mut mermaid = String::from("flowchart TD\n");
+        let mut node_ids = HashMap::new();
+        for (idx, (step, details)) in
+            self.graph_nodes(annotations).into_iter().enumerate()
+        {
+            let label = std::iter::once(step.as_str())
+                .chain(details.iter().map(String::as_str))
+                .map(|line| line.replace('"', "#quot;"))
+                .collect::<Vec<_>>()
+                .join("<br/>");
+            writeln!(mermaid, "  n{}[\"{}\"]", idx, label).unwrap();
+            node_ids.insert(step, idx);
+        }
+        for (blocker, step) in self.dependencies() {
+            writeln!(
+                mermaid,
+                "  n{} --> n{}",
+                node_ids[blocker], node_ids[step]
+            )
+            .unwrap();
+        }
+        mermaid
+    }
+
+    // Steps in alphabetical order with their annotations; steps blocked
+    // by a cycle have no position in the serial order
+    fn graph_nodes(
+        &self,
+        annotations: &GraphAnnotations,
+    ) -> Vec<(&Step, Vec<String>)> {
+        let mut positions = HashMap::new();
+        if annotations.serial_order {
+            let order = self.clone().complete_available_steps();
+            for (idx, step) in order.into_iter().enumerate() {
+                positions.insert(step, idx + 1);
+            }
+        }
+
+        self.names()
+            .into_iter()
+            .map(|step| {
+                let mut details = Vec::new();
+                if let Some(durations) = annotations.durations {
+                    details.push(format!("duration {}", durations(step)));
+                }
+                if let Some(position) = positions.get(step) {
+                    details.push(format!("order {}", position));
+                }
+                (step, details)
+            })
+            .collect()
+    }
+
+    pub fn check(&self) -> Result<(), StepError> {
+        // Steps left over after completing every step that can be done
+        // are blocked by a cycle or by steps downstream of one
+        let mut remaining = self.clone();
+        remaining.complete_available_steps();
+        let mut step = match remaining.pending.keys().min() {
+            Some(step) => step,
+            None => return Ok(()),
+        };
+
+        // Every remaining step has a remaining blocker, so following them
+        // eventually revisits a step
+        let mut path = vec![step];
+        loop {
+            step = remaining.pending[step].iter().min().unwrap();
+            if let Some(start) = path.iter().position(|&s| s == step) {
+                let mut cycle: Vec<Step> =
+                    path.split_off(start).into_iter().cloned().collect();
+                cycle.reverse();
+                cycle.insert(0, step.clone());
+                return Err(StepError::Cycle(cycle));
+            }
+            path.push(step);
+        }
+    }
+
+    // Completes steps one at a time in the serial order until none is
+    // available, returning them in that order; steps blocked by a cycle
+    // are left pending
+    fn complete_available_steps(&mut self) -> Vec<Step> {
         let mut order = Vec::new();
-        while let Some(step) = self.start_step() {
+        while let Some(step) = self.start_step_by(|_| Some(0)) {
+            self.complete_step(&step);
             order.push(step);
-            self.complete_step(step);
         }
-        order.iter().collect()
+        order
     }
 
-    fn start_step(&mut self) -> Option<Step> {
+    // Starts the available step with the highest priority, or the first
+    // alphabetically among equals; steps without a priority are skipped
+    fn start_step_by<F>(&mut self, priority: F) -> Option<Step>
+    where
+        F: Fn(&Step) -> Option<Time>,
+    {
         let step = self
             .pending
             .iter()
             .filter(|(_, blockers)| blockers.is_empty())
-            .map(|(step, _)| *step)
-            .min()?;
+            .filter_map(|(step, _)| Some((priority(step)?, Reverse(step))))
+            .max()?
+            .1
+             .0
+            .clone();
         self.pending.remove(&step);
         Some(step)
     }
 
-    fn complete_step(&mut self, step: Step) {
+    // Number of steps that each step directly blocks
+    fn dependents(&self) -> HashMap<&Step, Time> {
+        let mut dependents: HashMap<&Step, Time> =
+            self.pending.keys().map(|step| (step, 0)).collect();
+        for blocker in self.pending.values().flatten() {
+            *dependents.entry(blocker).or_insert(0) += 1;
+        }
+        dependents
+    }
+
+    // Longest chain of durations from each step to the end of the project
+    fn remaining_path_lengths<F>(
+        &self,
+        durations: F,
+    ) -> Result<HashMap<&Step, Time>, StepError>
+    where
+        F: Fn(&str) -> Time,
+    {
+        let order = self.clone().get_serial_order()?;
+        let mut lengths: HashMap<&Step, Time> = HashMap::new();
+        for step in order.iter().rev() {
+            let (step, blockers) = self.pending.get_key_value(step).unwrap();
+            let length =
+                lengths.get(step).cloned().unwrap_or(0) + durations(step);
+            lengths.insert(step, length);
+            for blocker in blockers {
+                let entry = lengths.entry(blocker).or_insert(0);
+                *entry = max(*entry, length);
+            }
+        }
+        Ok(lengths)
+    }
+
+    fn complete_step(&mut self, step: &str) {
         for blockers in self.pending.values_mut() {
-            blockers.remove(&step);
+            blockers.remove(step);
         }
     }
 }
@@ -49,10 +307,9 @@ impl FromStr for Steps {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let pattern = Regex::new(
-            r"^Step ([[:alpha:]]) must be .* step ([[:alpha:]]) can begin.$",
-        )
-        .unwrap();
+        let pattern =
+            Regex::new(r"^Step (\S+) must be .* step (\S+) can begin.$")
+                .unwrap();
 
         let mut pending = HashMap::new();
         for line in s.lines() {
@@ -60,63 +317,251 @@ impl FromStr for Steps {
             let step: Vec<Step> = captures
                 .iter()
                 .skip(1)
-                .map(|val| val.unwrap().as_str().chars().next().unwrap())
+                .map(|val| val.unwrap().as_str().to_string())
                 .collect();
 
-            pending.entry(step[0]).or_insert_with(HashSet::new);
+            pending.entry(step[0].clone()).or_insert_with(HashSet::new);
             pending
-                .entry(step[1])
-                .and_modify(|blockers| {
-                    blockers.insert(step[0]);
-                })
-                .or_insert_with(|| [step[0]].iter().cloned().collect());
+                .entry(step[1].clone())
+                .or_insert_with(HashSet::new)
+                .insert(step[0].clone());
         }
 
         Ok(Self { pending })
     }
 }
 
-type Time = u32;
+pub type Time = u32;
+
+// Durations of steps listed one per line as a name followed by a time
+#[derive(Clone, Debug)]
+pub struct DurationTable {
+    durations: HashMap<Step, Time>,
+}
+
+impl DurationTable {
+    pub fn get(&self, step: &str) -> Option<Time> {
+        self.durations.get(step).cloned()
+    }
+
+    pub fn check(&self, steps: &Steps) -> Result<(), String> {
+        match steps
+            .names()
+            .into_iter()
+            .find(|step| self.get(step).is_none())
+        {
+            Some(step) => Err(format!("No duration for step {}", step)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl FromStr for DurationTable {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut durations = HashMap::new();
+        for (line_num, line) in s.lines().enumerate() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.is_empty() {
+                continue;
+            }
+            let time = match fields[..] {
+                [_, time] => time.parse().ok(),
+                _ => None,
+            }
+            .ok_or_else(|| {
+                format!("Invalid duration on line {}", line_num + 1)
+            })?;
+            durations.insert(fields[0].to_string(), time);
+        }
+        Ok(Self { durations })
+    }
+}
+
+// Which available step a worker starts first
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Policy {
+    Alphabetical,
+    LongestDuration,
+    MostDependents,
+    CriticalPath,
+}
+
+impl Policy {
+    pub const ALL: [Policy; 4] = [
+        Policy::Alphabetical,
+        Policy::LongestDuration,
+        Policy::MostDependents,
+        Policy::CriticalPath,
+    ];
+
+    fn priorities(
+        self,
+        steps: &Steps,
+        durations: &dyn Fn(&str) -> Time,
+    ) -> Result<HashMap<Step, Time>, StepError> {
+        let priorities = match self {
+            Policy::Alphabetical => HashMap::new(),
+            Policy::LongestDuration => steps
+                .pending
+                .keys()
+                .map(|step| (step, durations(step)))
+                .collect(),
+            Policy::MostDependents => steps.dependents(),
+            Policy::CriticalPath => steps.remaining_path_lengths(durations)?,
+        };
+        Ok(priorities
+            .into_iter()
+            .map(|(step, priority)| (step.clone(), priority))
+            .collect())
+    }
+}
+
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Policy::Alphabetical => "alphabetical",
+            Policy::LongestDuration => "longest-duration",
+            Policy::MostDependents => "most-dependents",
+            Policy::CriticalPath => "critical-path",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Policy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Policy::ALL
+            .iter()
+            .find(|policy| policy.to_string() == s)
+            .cloned()
+            .ok_or_else(|| format!("Unknown policy '{}'", s))
+    }
+}
 
 pub struct Workers {
     steps: Steps,
     worker_state: Vec<Option<(Step, Time)>>,
     curr_time: Time,
     time_events: BTreeSet<Time>,
-    base_step_time: u32,
+    durations: Box<dyn Fn(&str) -> Time>,
+    policy: Policy,
+    priorities: HashMap<Step, Time>,
+    capabilities: Vec<Option<HashSet<Step>>>,
+    schedule: Schedule,
 }
 
 impl Workers {
     pub fn new(steps: Steps, num_workers: usize, base_step_time: u32) -> Self {
+        Workers::with_durations(steps, num_workers, move |step| {
+            base_step_time + letter_duration(step)
+        })
+    }
+
+    pub fn with_durations<F>(
+        steps: Steps,
+        num_workers: usize,
+        durations: F,
+    ) -> Self
+    where
+        F: Fn(&str) -> Time + 'static,
+    {
         Workers {
             steps,
             worker_state: vec![None; num_workers],
             time_events: [0].iter().cloned().collect(),
             curr_time: 0,
-            base_step_time,
+            durations: Box::new(durations),
+            policy: Policy::Alphabetical,
+            priorities: HashMap::new(),
+            capabilities: vec![None; num_workers],
+            schedule: Schedule {
+                num_workers,
+                assignments: Vec::new(),
+            },
+        }
+    }
+
+    pub fn set_policy(&mut self, policy: Policy) {
+        self.policy = policy;
+    }
+
+    // Limits the steps a worker can do; workers can do any step by default
+    pub fn restrict_worker(
+        &mut self,
+        worker: usize,
+        steps: HashSet<Step>,
+    ) -> Result<(), StepError> {
+        if let Some(step) = steps
+            .iter()
+            .filter(|step| !self.steps.pending.contains_key(*step))
+            .min()
+        {
+            return Err(StepError::UnknownStep(step.clone()));
         }
+        let capabilities = self
+            .capabilities
+            .get_mut(worker)
+            .ok_or(StepError::UnknownWorker(worker))?;
+        *capabilities = Some(steps);
+        Ok(())
     }
 
-    pub fn time_all_steps(&mut self) -> Time {
+    pub fn time_all_steps(&mut self) -> Result<Time, StepError> {
+        self.steps.check()?;
+        if let Some(step) = self.steps.names().into_iter().find(|step| {
+            !self
+                .capabilities
+                .iter()
+                .any(|steps| steps.as_ref().is_none_or(|s| s.contains(*step)))
+        }) {
+            return Err(StepError::NoCapableWorker(step.clone()));
+        }
+        self.priorities =
+            self.policy.priorities(&self.steps, &*self.durations)?;
+
         while let Some(time) = self.time_events.iter().next() {
             self.curr_time = *time;
             self.time_events.remove(&self.curr_time);
             self.complete_steps();
             self.start_steps();
         }
-        self.curr_time
+        Ok(self.curr_time)
     }
 
-    fn start_steps(&mut self) {
-        let end_time = self.curr_time + self.base_step_time;
+    pub fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
 
-        for worker in self.worker_state.iter_mut().filter(|w| w.is_none()) {
-            if let Some(step) = self.steps.start_step() {
-                let step_end = end_time + step_time(step);
+    fn start_steps(&mut self) {
+        for (idx, worker) in self
+            .worker_state
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, w)| w.is_none())
+        {
+            let capabilities = self.capabilities[idx].as_ref();
+            let priorities = &self.priorities;
+            let step = self.steps.start_step_by(|step| {
+                if capabilities.is_none_or(|steps| steps.contains(step)) {
+                    Some(priorities.get(step).cloned().unwrap_or(0))
+                } else {
+                    None
+                }
+            });
+            if let Some(step) = step {
+                let step_end = self.curr_time + (self.durations)(&step);
                 self.time_events.insert(step_end);
-                *worker = Some((step, step_end));
-            } else {
-                break;
+                *worker = Some((step.clone(), step_end));
+                self.schedule.assignments.push(Assignment {
+                    worker: idx,
+                    step,
+                    start: self.curr_time,
+                    end: step_end,
+                });
             }
         }
     }
@@ -127,10 +572,279 @@ impl Workers {
         for worker in self.worker_state.iter_mut() {
             if let Some((step, end_time)) = worker {
                 if *end_time == curr_time {
-                    self.steps.complete_step(*step);
+                    self.steps.complete_step(step);
                     *worker = None;
                 }
             }
         }
     }
 }
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Assignment {
+    pub worker: usize,
+    pub step: Step,
+    pub start: Time,
+    pub end: Time,
+}
+
+const SVG_ROW_HEIGHT: u32 = 30;
+const SVG_LABEL_WIDTH: u32 = 80;
+const SVG_CHART_WIDTH: f64 = 1000.0;
+
+// Every step assigned to a worker, in the order they were started
+#[derive(Clone, Debug)]
+pub struct Schedule {
+    pub num_workers: usize,
+    pub assignments: Vec<Assignment>,
+}
+
+impl Schedule {
+    pub fn total_time(&self) -> Time {
+        self.assignments.iter().map(|a| a.end).max().unwrap_or(0)
+    }
+
+    // Table with one row per second, as shown in the puzzle
+    pub fn to_table(&self) -> String {
+        let mut header = String::from("Second");
+        let mut columns = Vec::new();
+        for worker in 1..=self.num_workers {
+            header.push_str("   ");
+            columns.push(header.len() + 3);
+            write!(header, "Worker {}", worker).unwrap();
+        }
+        header.push_str("   ");
+        let done_column = header.len();
+        header.push_str("Done");
+
+        let mut completed = self.assignments.clone();
+        completed.sort_by_key(|a| (a.end, a.worker));
+
+        let mut table = header;
+        table.push('\n');
+        for second in 0..=self.total_time() {
+            let mut row = format!("{:>4}", second);
+            for (worker, column) in columns.iter().enumerate() {
+                let step = self
+                    .assignments
+                    .iter()
+                    .find(|a| {
+                        a.worker == worker
+                            && a.start <= second
+                            && second < a.end
+                    })
+                    .map_or(".", |a| a.step.as_str());
+                pad_to(&mut row, *column);
+                row.push_str(step);
+            }
+            pad_to(&mut row, done_column);
+            row.push_str(&format_steps(
+                completed
+                    .iter()
+                    .take_while(|a| a.end <= second)
+                    .map(|a| &a.step),
+            ));
+            table.push_str(row.trim_end());
+            table.push('\n');
+        }
+        table
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("worker,step,start,end\n");
+        for assignment in self.assignments.iter() {
+            writeln!(
+                csv,
+                "{},{},{},{}",
+                assignment.worker + 1,
+                csv_field(&assignment.step),
+                assignment.start,
+                assignment.end
+            )
+            .unwrap();
+        }
+        csv
+    }
+
+    // Gantt chart with one row per worker and a bar per step
+    pub fn to_svg(&self) -> String {
+        let total_time = max(self.total_time(), 1);
+        let scale = SVG_CHART_WIDTH / f64::from(total_time);
+        let width = SVG_LABEL_WIDTH + SVG_CHART_WIDTH as u32 + 20;
+        let axis_y = SVG_ROW_HEIGHT * (self.num_workers as u32 + 1);
+        let height = axis_y + 30;
+        let x_pos =
+            |time: Time| f64::from(SVG_LABEL_WIDTH) + scale * f64::from(time);
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" \
+             height=\"{}\" font-family=\"sans-serif\" font-size=\"12\">",
+            width, height
+        )
+        .unwrap();
+        for worker in 0..self.num_workers as u32 {
+            writeln!(
+                svg,
+                "  <text x=\"5\" y=\"{}\">Worker {}</text>",
+                SVG_ROW_HEIGHT * (worker + 1) - 10,
+                worker + 1
+            )
+            .unwrap();
+        }
+        for assignment in self.assignments.iter() {
+            let top = SVG_ROW_HEIGHT * assignment.worker as u32 + 5;
+            let (left, right) =
+                (x_pos(assignment.start), x_pos(assignment.end));
+            writeln!(
+                svg,
+                "  <rect x=\"{:.1}\" y=\"{}\" width=\"{:.1}\" height=\"{}\" \
+                 fill=\"#8cb4e8\" stroke=\"#1f4e8c\"><title>{}: {}-{}</title>\
+                 </rect>",
+                left,
+                top,
+                right - left,
+                SVG_ROW_HEIGHT - 10,
+                xml_escape(&assignment.step),
+                assignment.start,
+                assignment.end
+            )
+            .unwrap();
+            writeln!(
+                svg,
+                "  <text x=\"{:.1}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+                (left + right) / 2.0,
+                top + SVG_ROW_HEIGHT / 2,
+                xml_escape(&assignment.step)
+            )
+            .unwrap();
+        }
+
+        writeln!(
+            svg,
+            "  <line x1=\"{}\" y1=\"{}\" x2=\"{:.1}\" y2=\"{}\" \
+             stroke=\"black\"/>",
+            SVG_LABEL_WIDTH,
+            axis_y,
+            x_pos(total_time),
+            axis_y
+        )
+        .unwrap();
+        let tick_step = max(1, total_time / 10);
+        for time in (0..=total_time).step_by(tick_step as usize) {
+            writeln!(
+                svg,
+                "  <text x=\"{:.1}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+                x_pos(time),
+                axis_y + 20,
+                time
+            )
+            .unwrap();
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+// Long step names may push the line past the column, but are still
+// separated by a space from what comes next
+fn pad_to(line: &mut String, column: usize) {
+    line.push(' ');
+    while line.len() < column {
+        line.push(' ');
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn dot_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub struct Analysis {
+    pub critical_path: Vec<Step>,
+    pub lower_bound: Time,
+    pub completion_times: Vec<Time>,
+}
+
+impl Analysis {
+    // Completion times are simulated for every number of workers from 1 to
+    // max_workers, while the critical path gives the time with unlimited
+    // workers
+    pub fn new<F>(
+        steps: &Steps,
+        max_workers: usize,
+        policy: Policy,
+        durations: F,
+    ) -> Result<Self, StepError>
+    where
+        F: Fn(&str) -> Time + 'static,
+    {
+        let durations = Rc::new(durations);
+        let (critical_path, lower_bound) = steps.critical_path(&*durations)?;
+        let completion_times = (1..=max_workers)
+            .map(|num_workers| {
+                let durations = Rc::clone(&durations);
+                let mut workers = Workers::with_durations(
+                    steps.clone(),
+                    num_workers,
+                    move |step| durations(step),
+                );
+                workers.set_policy(policy);
+                workers.time_all_steps()
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Analysis {
+            critical_path,
+            lower_bound,
+            completion_times,
+        })
+    }
+
+    // Fewest workers needed to finish as soon as any simulated number of
+    // workers does; more workers than this would stay idle
+    pub fn useful_workers(&self) -> Option<usize> {
+        let best = self.completion_times.iter().min()?;
+        self.completion_times
+            .iter()
+            .position(|time| time == best)
+            .map(|idx| idx + 1)
+    }
+
+    pub fn to_table(&self) -> String {
+        let useful_workers = self
+            .useful_workers()
+            .map_or("-".to_string(), |workers| workers.to_string());
+        let mut table = format!(
+            "Critical path: {}\nLower bound: {}\nUseful workers: {}\n\n\
+             Workers   Time\n",
+            self.critical_path.join(" -> "),
+            self.lower_bound,
+            useful_workers
+        );
+        for (idx, time) in self.completion_times.iter().enumerate() {
+            write!(table, "{:>7}   {:>4}", idx + 1, time).unwrap();
+            if *time == self.lower_bound {
+                table.push_str("   (lower bound)");
+            }
+            table.push('\n');
+        }
+        table
+    }
+}
@@ -1,8 +1,13 @@
 use clap::{crate_description, App, Arg};
-use day07::{Steps, Workers};
-use std::fs::File;
+use day07::{
+    format_steps, letter_duration, Analysis, DurationTable, GraphAnnotations,
+    Policy, Steps, Time, Workers,
+};
+use std::fs::{self, File};
 use std::io::Read;
 use std::process::exit;
+use std::rc::Rc;
+use std::str::FromStr;
 
 const NUM_WORKERS: usize = 5;
 const BASE_STEP_TIME: u32 = 60;
@@ -15,20 +20,153 @@ fn main() {
                 .required(true)
                 .index(1),
         )
+        .arg(
+            Arg::with_name("durations")
+                .long("durations")
+                .value_name("FILE")
+                .help("Reads the duration of each step from a file"),
+        )
+        .arg(
+            Arg::with_name("policy")
+                .long("policy")
+                .value_name("POLICY")
+                .possible_values(&[
+                    "alphabetical",
+                    "longest-duration",
+                    "most-dependents",
+                    "critical-path",
+                ])
+                .help("Sets which available step workers start first"),
+        )
+        .arg(
+            Arg::with_name("analysis")
+                .long("analysis")
+                .value_name("MAX_WORKERS")
+                .help("Shows the critical path and completion time by workers"),
+        )
+        .arg(
+            Arg::with_name("graph")
+                .long("graph")
+                .value_name("FILE")
+                .help("Writes the dependency graph in Graphviz DOT format"),
+        )
+        .arg(
+            Arg::with_name("mermaid")
+                .long("mermaid")
+                .requires("graph")
+                .help("Writes the dependency graph as a Mermaid flowchart"),
+        )
+        .arg(
+            Arg::with_name("annotate")
+                .long("annotate")
+                .requires("graph")
+                .help("Shows durations and serial order in the graph"),
+        )
+        .arg(
+            Arg::with_name("table")
+                .long("table")
+                .help("Prints what each worker does every second"),
+        )
+        .arg(
+            Arg::with_name("csv")
+                .long("csv")
+                .value_name("FILE")
+                .help("Writes the worker schedule as CSV"),
+        )
+        .arg(
+            Arg::with_name("svg")
+                .long("svg")
+                .value_name("FILE")
+                .help("Writes a Gantt chart of the worker schedule as SVG"),
+        )
         .get_matches();
 
     println!(crate_description!());
-    let mut steps = read_input(args.value_of("INPUT").unwrap());
-    let mut workers = Workers::new(steps.clone(), NUM_WORKERS, BASE_STEP_TIME);
-    println!("Part 1: {}", steps.get_serial_order());
-    println!("Part 2: {}", workers.time_all_steps());
+    let mut steps: Steps = read_input(args.value_of("INPUT").unwrap());
+    let durations: Rc<dyn Fn(&str) -> Time> = match args.value_of("durations") {
+        Some(filename) => {
+            let durations: DurationTable = read_input(filename);
+            if let Err(err) = durations.check(&steps) {
+                println!("Invalid durations: {}", err);
+                exit(5);
+            }
+            Rc::new(move |step| durations.get(step).unwrap())
+        }
+        None => Rc::new(|step| BASE_STEP_TIME + letter_duration(step)),
+    };
+    if let Some(filename) = args.value_of("graph") {
+        let annotations = if args.is_present("annotate") {
+            GraphAnnotations {
+                durations: Some(&*durations),
+                serial_order: true,
+            }
+        } else {
+            GraphAnnotations::default()
+        };
+        let graph = if args.is_present("mermaid") {
+            steps.to_mermaid(&annotations)
+        } else {
+            steps.to_dot(&annotations)
+        };
+        write_output(filename, &graph);
+    }
+
+    let policy = args
+        .value_of("policy")
+        .map_or(Policy::Alphabetical, |value| value.parse().unwrap());
+    let max_workers = args.value_of("analysis").map(|value| {
+        value.parse::<usize>().unwrap_or_else(|err| {
+            println!("Invalid number of workers '{}': {}", value, err);
+            exit(1);
+        })
+    });
+
+    let analysis = max_workers.map(|max_workers| {
+        let durations = Rc::clone(&durations);
+        Analysis::new(&steps, max_workers, policy, move |step| durations(step))
+    });
+    let mut workers =
+        Workers::with_durations(steps.clone(), NUM_WORKERS, move |step| {
+            durations(step)
+        });
+    workers.set_policy(policy);
+    match steps.get_serial_order() {
+        Ok(order) => println!("Part 1: {}", format_steps(order.iter())),
+        Err(err) => {
+            println!("Invalid instructions: {}", err);
+            exit(5);
+        }
+    }
+    match workers.time_all_steps() {
+        Ok(time) => println!("Part 2: {}", time),
+        Err(err) => {
+            println!("Invalid instructions: {}", err);
+            exit(5);
+        }
+    }
+
+    if let Some(analysis) = analysis {
+        // Instructions were already validated by the steps above
+        print!("{}", analysis.unwrap().to_table());
+    }
+
+    let schedule = workers.schedule();
+    if args.is_present("table") {
+        print!("{}", schedule.to_table());
+    }
+    if let Some(filename) = args.value_of("csv") {
+        write_output(filename, &schedule.to_csv());
+    }
+    if let Some(filename) = args.value_of("svg") {
+        write_output(filename, &schedule.to_svg());
+    }
 }
 
-fn read_input(filename: &str) -> Steps {
+fn read_input<T: FromStr<Err = String>>(filename: &str) -> T {
     let mut file = match File::open(filename) {
         Ok(file) => file,
         Err(err) => {
-            println!("Failed to open file '{}': {}", filename, err.to_string());
+            println!("Failed to open file '{}': {}", filename, err);
             exit(2);
         }
     };
@@ -37,11 +175,7 @@ fn read_input(filename: &str) -> Steps {
     match file.read_to_string(&mut contents) {
         Ok(_) => (),
         Err(err) => {
-            println!(
-                "Failed to read input file '{}': {}",
-                filename,
-                err.to_string()
-            );
+            println!("Failed to read input file '{}': {}", filename, err);
             exit(3);
         }
     };
@@ -54,3 +188,10 @@ fn read_input(filename: &str) -> Steps {
         }
     }
 }
+
+fn write_output(filename: &str, contents: &str) {
+    if let Err(err) = fs::write(filename, contents) {
+        println!("Failed to write output file '{}': {}", filename, err);
+        exit(6);
+    }
+}
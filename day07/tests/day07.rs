@@ -1,22 +1,278 @@
-use day07::{Steps, Workers};
+use day07::{
+    format_steps, letter_duration, Analysis, Assignment, DurationTable,
+    GraphAnnotations, Policy, StepError, Steps, Workers,
+};
+
+fn names(steps: &[&str]) -> Vec<String> {
+    steps.iter().map(|step| step.to_string()).collect()
+}
+
+const EXAMPLE: &str = "Step C must be finished before step A can begin.
+Step C must be finished before step F can begin.
+Step A must be finished before step B can begin.
+Step A must be finished before step D can begin.
+Step B must be finished before step E can begin.
+Step D must be finished before step E can begin.
+Step F must be finished before step E can begin.";
 
 #[test]
 fn examples() {
+    let mut steps: Steps = EXAMPLE.parse().unwrap();
+    let num_workers = 2;
+    let base_step_time = 0;
+    let mut workers = Workers::new(steps.clone(), num_workers, base_step_time);
+    assert_eq!(
+        format_steps(steps.get_serial_order().unwrap().iter()),
+        "CABDFE"
+    );
+    assert_eq!(workers.time_all_steps().unwrap(), 15);
+}
+
+#[test]
+fn cycle() {
     let input = [
         "Step C must be finished before step A can begin.",
-        "Step C must be finished before step F can begin.",
         "Step A must be finished before step B can begin.",
-        "Step A must be finished before step D can begin.",
-        "Step B must be finished before step E can begin.",
+        "Step B must be finished before step D can begin.",
+        "Step D must be finished before step A can begin.",
         "Step D must be finished before step E can begin.",
-        "Step F must be finished before step E can begin.",
     ]
     .join("\n");
 
     let mut steps: Steps = input.parse().unwrap();
-    let num_workers = 2;
-    let base_step_time = 0;
-    let mut workers = Workers::new(steps.clone(), num_workers, base_step_time);
-    assert_eq!(steps.get_serial_order(), "CABDFE");
-    assert_eq!(workers.time_all_steps(), 15);
+    let mut workers = Workers::new(steps.clone(), 2, 0);
+    let cycle = StepError::Cycle(names(&["A", "B", "D", "A"]));
+    assert_eq!(steps.get_serial_order(), Err(cycle.clone()));
+    assert_eq!(workers.time_all_steps(), Err(cycle));
+}
+
+#[test]
+fn self_dependency() {
+    let input = "Step A must be finished before step A can begin.";
+    let steps: Steps = input.parse().unwrap();
+    assert_eq!(steps.check(), Err(StepError::Cycle(names(&["A", "A"]))));
+}
+
+#[test]
+fn schedule() {
+    let steps: Steps = EXAMPLE.parse().unwrap();
+    let mut workers = Workers::new(steps, 2, 0);
+    workers.time_all_steps().unwrap();
+    let schedule = workers.schedule();
+    assert_eq!(schedule.total_time(), 15);
+    assert_eq!(
+        schedule.assignments[1],
+        Assignment {
+            worker: 0,
+            step: "A".to_string(),
+            start: 3,
+            end: 4,
+        }
+    );
+    assert_eq!(
+        schedule.to_table(),
+        "Second   Worker 1   Worker 2   Done
+   0        C          .
+   1        C          .
+   2        C          .
+   3        A          F       C
+   4        B          F       CA
+   5        B          F       CA
+   6        D          F       CAB
+   7        D          F       CAB
+   8        D          F       CAB
+   9        D          .       CABF
+  10        E          .       CABFD
+  11        E          .       CABFD
+  12        E          .       CABFD
+  13        E          .       CABFD
+  14        E          .       CABFD
+  15        .          .       CABFDE
+"
+    );
+    assert_eq!(
+        schedule.to_csv(),
+        "worker,step,start,end
+1,C,0,3
+1,A,3,4
+2,F,3,9
+1,B,4,6
+1,D,6,10
+1,E,10,15
+"
+    );
+}
+
+#[test]
+fn named_steps() {
+    let input = [
+        "Step checkout must be finished before step build can begin.",
+        "Step checkout must be finished before step lint can begin.",
+        "Step build must be finished before step test can begin.",
+        "Step build must be finished before step package can begin.",
+        "Step test must be finished before step deploy can begin.",
+        "Step package must be finished before step deploy can begin.",
+    ]
+    .join("\n");
+    let durations = "checkout 2
+                     build 10
+                     lint 4
+
+                     test 7
+                     package 3
+                     deploy 1";
+
+    let mut steps: Steps = input.parse().unwrap();
+    let durations: DurationTable = durations.parse().unwrap();
+    durations.check(&steps).unwrap();
+    let mut single =
+        Workers::with_durations(steps.clone(), 1, |step| step.len() as u32);
+    let mut workers = Workers::with_durations(steps.clone(), 2, move |step| {
+        durations.get(step).unwrap()
+    });
+    let order = steps.get_serial_order().unwrap();
+    assert_eq!(
+        order,
+        names(&["checkout", "build", "lint", "package", "test", "deploy"])
+    );
+    assert_eq!(
+        format_steps(order.iter()),
+        "checkout,build,lint,package,test,deploy"
+    );
+    assert_eq!(workers.time_all_steps().unwrap(), 20);
+    assert_eq!(single.time_all_steps().unwrap(), 34);
+}
+
+#[test]
+fn missing_durations() {
+    let input = "Step a must be finished before step b can begin.";
+    let steps: Steps = input.parse().unwrap();
+    let durations: DurationTable = "a 1".parse().unwrap();
+    assert_eq!(
+        durations.check(&steps),
+        Err("No duration for step b".to_string())
+    );
+    assert!("a 1 2".parse::<DurationTable>().is_err());
+    assert!("a x".parse::<DurationTable>().is_err());
+}
+
+#[test]
+fn analysis() {
+    let steps: Steps = EXAMPLE.parse().unwrap();
+    let analysis =
+        Analysis::new(&steps, 4, Policy::Alphabetical, letter_duration)
+            .unwrap();
+    assert_eq!(analysis.critical_path, names(&["C", "F", "E"]));
+    assert_eq!(analysis.lower_bound, 14);
+    assert_eq!(analysis.completion_times, vec![21, 15, 14, 14]);
+    assert_eq!(analysis.useful_workers(), Some(3));
+    assert_eq!(
+        analysis.to_table(),
+        "Critical path: C -> F -> E
+Lower bound: 14
+Useful workers: 3
+
+Workers   Time
+      1     21
+      2     15
+      3     14   (lower bound)
+      4     14   (lower bound)
+"
+    );
+}
+
+#[test]
+fn policies() {
+    let steps: Steps = EXAMPLE.parse().unwrap();
+    let expected = [
+        (Policy::Alphabetical, "CAFBDE"),
+        (Policy::LongestDuration, "CFADBE"),
+        (Policy::MostDependents, "CAFBDE"),
+        (Policy::CriticalPath, "CFADBE"),
+    ];
+    for (policy, order) in expected.iter() {
+        let mut workers = Workers::new(steps.clone(), 2, 0);
+        workers.set_policy(*policy);
+        assert_eq!(workers.time_all_steps().unwrap(), 15);
+        let started = workers.schedule().assignments.iter().map(|a| &a.step);
+        assert_eq!(format_steps(started), *order);
+    }
+    assert_eq!(
+        "critical-path".parse::<Policy>().unwrap(),
+        Policy::CriticalPath
+    );
+
+    let mut workers = Workers::new(steps.clone(), 2, 0);
+    workers
+        .restrict_worker(0, names(&["C", "E"]).into_iter().collect())
+        .unwrap();
+    assert_eq!(workers.time_all_steps().unwrap(), 21);
+
+    let mut workers = Workers::new(steps.clone(), 2, 0);
+    workers
+        .restrict_worker(0, names(&["A"]).into_iter().collect())
+        .unwrap();
+    workers
+        .restrict_worker(1, names(&["A", "C"]).into_iter().collect())
+        .unwrap();
+    assert_eq!(
+        workers.time_all_steps(),
+        Err(StepError::NoCapableWorker("B".to_string()))
+    );
+
+    let mut workers = Workers::new(steps, 1, 0);
+    assert_eq!(
+        workers.restrict_worker(0, names(&["A", "X"]).into_iter().collect()),
+        Err(StepError::UnknownStep("X".to_string()))
+    );
+    assert_eq!(
+        workers.restrict_worker(3, names(&["A"]).into_iter().collect()),
+        Err(StepError::UnknownWorker(3))
+    );
+    assert_eq!(workers.time_all_steps().unwrap(), 21);
+}
+
+#[test]
+fn graph_export() {
+    let input = [
+        "Step C must be finished before step A can begin.",
+        "Step C must be finished before step F can begin.",
+        "Step A must be finished before step B can begin.",
+        "Step F must be finished before step B can begin.",
+    ]
+    .join("\n");
+
+    let steps: Steps = input.parse().unwrap();
+    assert_eq!(
+        steps.to_dot(&GraphAnnotations::default()),
+        "digraph steps {
+  \"A\" [label=\"A\"];
+  \"B\" [label=\"B\"];
+  \"C\" [label=\"C\"];
+  \"F\" [label=\"F\"];
+  \"A\" -> \"B\";
+  \"C\" -> \"A\";
+  \"C\" -> \"F\";
+  \"F\" -> \"B\";
+}
+"
+    );
+
+    let annotations = GraphAnnotations {
+        durations: Some(&letter_duration),
+        serial_order: true,
+    };
+    assert_eq!(
+        steps.to_mermaid(&annotations),
+        "flowchart TD
+  n0[\"A<br/>duration 1<br/>order 2\"]
+  n1[\"B<br/>duration 2<br/>order 4\"]
+  n2[\"C<br/>duration 3<br/>order 1\"]
+  n3[\"F<br/>duration 6<br/>order 3\"]
+  n0 --> n1
+  n2 --> n0
+  n2 --> n3
+  n3 --> n1
+"
+    );
 }
@@ -1,18 +1,124 @@
+use std::cmp::max;
 use std::collections::HashMap;
-use std::slice::Iter;
+use std::fmt::{self, Write};
+use std::io::{Error, ErrorKind};
 
-type NodeID = u32;
+pub type NodeID = u32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    UnexpectedEnd(usize),
+    TrailingData(usize),
+}
+
+impl ParseError {
+    pub fn index(&self) -> usize {
+        match *self {
+            ParseError::UnexpectedEnd(index)
+            | ParseError::TrailingData(index) => index,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match self {
+            ParseError::UnexpectedEnd(_) => "input ends before this node",
+            ParseError::TrailingData(_) => "unexpected data after the tree",
+        };
+        write!(f, "Token {}: {}", self.index(), description)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+struct Tokens<'a> {
+    input: &'a [u32],
+    pos: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn remaining(&self) -> usize {
+        self.input.len() - self.pos
+    }
+
+    fn next(&mut self) -> Option<u32> {
+        let token = *self.input.get(self.pos)?;
+        self.pos += 1;
+        Some(token)
+    }
+
+    fn take(&mut self, count: usize) -> Option<&'a [u32]> {
+        if count > self.remaining() {
+            return None;
+        }
+        self.pos += count;
+        Some(&self.input[self.pos - count..self.pos])
+    }
+}
 
 struct Node {
     num_child: usize,
     num_meta: usize,
+    // Position of the node's child and metadata counts in the input
+    header: usize,
     child: Vec<NodeID>,
-    metadata: Vec<u8>,
+    metadata: Vec<u32>,
+}
+
+// Properties of a node that depend on the rest of the tree
+#[derive(Clone, Default)]
+struct Summary {
+    parent: Option<NodeID>,
+    depth: usize,
+    height: usize,
+    subtree_metadata: u64,
+    value: Option<u64>,
 }
 
 pub struct NodeGraph {
     nodes: HashMap<NodeID, Node>,
     next_node_id: NodeID,
+    summaries: Vec<Summary>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Visit {
+    Enter(NodeID),
+    Exit(NodeID),
+}
+
+// Depth-first walk that enters each node before its children and exits it
+// after them, without recursing
+struct Walk<'a> {
+    graph: &'a NodeGraph,
+    stack: Vec<(NodeID, Option<usize>)>,
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = Visit;
+
+    fn next(&mut self) -> Option<Visit> {
+        loop {
+            let (node_id, visited) = self.stack.last_mut()?;
+            let node_id = *node_id;
+            let children = &self.graph.nodes[&node_id].child;
+            match *visited {
+                None => {
+                    *visited = Some(0);
+                    return Some(Visit::Enter(node_id));
+                }
+                Some(idx) if idx < children.len() => {
+                    *visited = Some(idx + 1);
+                    self.stack.push((children[idx], None));
+                }
+                Some(_) => {
+                    self.stack.pop();
+                    return Some(Visit::Exit(node_id));
+                }
+            }
+        }
+    }
 }
 
 impl NodeGraph {
@@ -20,24 +126,35 @@ impl NodeGraph {
         NodeGraph {
             nodes: HashMap::new(),
             next_node_id: 0,
+            summaries: Vec::new(),
         }
     }
 
-    pub fn build_graph(input: &[u8]) -> Self {
-        let mut input_iter = input.iter();
+    pub fn build_graph(input: &[u32]) -> Result<Self, ParseError> {
+        let mut tokens = Tokens { input, pos: 0 };
         let mut stack = Vec::new();
         let mut graph = Self::new();
 
+        // Errors point at the header of the node that cannot be completed
         loop {
-            let num_child = *input_iter.next().expect("Invalid input") as usize;
-            let num_meta = *input_iter.next().expect("Invalid input") as usize;
-            let mut node_id = graph.add_node(num_child, num_meta);
+            let header = tokens.pos;
+            let end = ParseError::UnexpectedEnd(header);
+            let num_child = tokens.next().ok_or(end)? as usize;
+            let num_meta = tokens.next().ok_or(end)? as usize;
+            // Every child takes at least two numbers, so larger counts could
+            // never be satisfied by the rest of the input
+            if 2 * num_child as u64 + num_meta as u64
+                > tokens.remaining() as u64
+            {
+                return Err(end);
+            }
+            let mut node_id = graph.add_node(num_child, num_meta, header);
             if num_child > 0 {
                 stack.push(node_id);
                 continue;
             }
             loop {
-                graph.add_metadata(node_id, &mut input_iter);
+                graph.add_metadata(node_id, &mut tokens)?;
                 if let Some(parent_id) = stack.pop() {
                     graph.add_child(parent_id, node_id);
                     if graph.missing_child(parent_id) {
@@ -45,37 +162,225 @@ impl NodeGraph {
                         break;
                     }
                     node_id = parent_id;
+                } else if tokens.remaining() > 0 {
+                    return Err(ParseError::TrailingData(tokens.pos));
                 } else {
-                    return graph;
+                    graph.compute_summaries();
+                    return Ok(graph);
                 }
             }
         }
     }
 
-    pub fn sum_metadata(&self) -> u32 {
+    pub fn sum_metadata(&self) -> u64 {
         self.nodes.values().map(|node| node.sum_metadata()).sum()
     }
 
-    pub fn total_value(&self) -> u32 {
-        let mut stack = Vec::new();
-        let mut total = 0;
+    pub fn total_value(&self) -> Result<u64, Error> {
+        self.node_value(0).ok_or_else(|| {
+            Error::new(ErrorKind::InvalidData, "Value too large")
+        })
+    }
+
+    // None for unknown nodes and for values that do not fit in a u64
+    pub fn node_value(&self, node_id: NodeID) -> Option<u64> {
+        self.summary(node_id)?.value
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn root(&self) -> Option<NodeID> {
+        if self.nodes.is_empty() {
+            None
+        } else {
+            Some(0)
+        }
+    }
 
-        stack.push(0);
-        while let Some(node_id) = stack.pop() {
+    pub fn children(&self, node_id: NodeID) -> Option<&[NodeID]> {
+        self.nodes.get(&node_id).map(|node| node.child.as_slice())
+    }
+
+    pub fn metadata(&self, node_id: NodeID) -> Option<&[u32]> {
+        self.nodes
+            .get(&node_id)
+            .map(|node| node.metadata.as_slice())
+    }
+
+    pub fn parent(&self, node_id: NodeID) -> Option<NodeID> {
+        self.summary(node_id)?.parent
+    }
+
+    // Number of edges from the root to the node
+    pub fn depth(&self, node_id: NodeID) -> Option<usize> {
+        self.summary(node_id).map(|summary| summary.depth)
+    }
+
+    // Number of edges from the node to its deepest descendant
+    pub fn height(&self, node_id: NodeID) -> Option<usize> {
+        self.summary(node_id).map(|summary| summary.height)
+    }
+
+    pub fn subtree_metadata(&self, node_id: NodeID) -> Option<u64> {
+        self.summary(node_id)
+            .map(|summary| summary.subtree_metadata)
+    }
+
+    pub fn pre_order(&self) -> impl Iterator<Item = NodeID> + '_ {
+        self.walk().filter_map(|visit| match visit {
+            Visit::Enter(node_id) => Some(node_id),
+            Visit::Exit(_) => None,
+        })
+    }
+
+    pub fn post_order(&self) -> impl Iterator<Item = NodeID> + '_ {
+        self.walk().filter_map(|visit| match visit {
+            Visit::Enter(_) => None,
+            Visit::Exit(node_id) => Some(node_id),
+        })
+    }
+
+    // Flat list of numbers in the license file format
+    pub fn to_numbers(&self) -> Vec<u32> {
+        let mut numbers = Vec::new();
+        for visit in self.walk() {
+            match visit {
+                Visit::Enter(node_id) => {
+                    let node = &self.nodes[&node_id];
+                    numbers.push(node.num_child as u32);
+                    numbers.push(node.num_meta as u32);
+                }
+                Visit::Exit(node_id) => {
+                    numbers.extend_from_slice(&self.nodes[&node_id].metadata);
+                }
+            }
+        }
+        numbers
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        for visit in self.walk() {
+            match visit {
+                Visit::Enter(node_id) => {
+                    if json.ends_with('}') {
+                        json.push(',');
+                    }
+                    let metadata: Vec<String> = self.nodes[&node_id]
+                        .metadata
+                        .iter()
+                        .map(|val| val.to_string())
+                        .collect();
+                    write!(
+                        json,
+                        "{{\"id\":{},\"metadata\":[{}],\"value\":{},\
+                         \"children\":[",
+                        node_id,
+                        metadata.join(","),
+                        self.node_value(node_id)
+                            .map_or("null".to_string(), |v| v.to_string())
+                    )
+                    .unwrap();
+                }
+                Visit::Exit(_) => json.push_str("]}"),
+            }
+        }
+        json
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph tree {\n");
+        for node_id in self.pre_order() {
+            let metadata: Vec<String> = self.nodes[&node_id]
+                .metadata
+                .iter()
+                .map(|val| val.to_string())
+                .collect();
+            writeln!(
+                dot,
+                "    {} [label=\"{}\\nmetadata {}\\nvalue {}\"];",
+                node_id,
+                node_id,
+                metadata.join(" "),
+                self.node_value(node_id)
+                    .map_or("too large".to_string(), |v| v.to_string())
+            )
+            .unwrap();
+        }
+        for node_id in self.pre_order() {
+            for child_id in self.nodes[&node_id].child.iter() {
+                writeln!(dot, "    {} -> {};", node_id, child_id).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn walk(&self) -> Walk<'_> {
+        Walk {
+            graph: self,
+            stack: self.root().map(|root| (root, None)).into_iter().collect(),
+        }
+    }
+
+    fn summary(&self, node_id: NodeID) -> Option<&Summary> {
+        self.summaries.get(node_id as usize)
+    }
+
+    // Children are always added after their parent, so visiting nodes in
+    // reverse order computes every summary from already known children and
+    // visiting them in order does the same from known parents; values that
+    // would overflow are left unknown
+    fn compute_summaries(&mut self) {
+        let mut summaries =
+            vec![Summary::default(); self.next_node_id as usize];
+        for node_id in (0..self.next_node_id).rev() {
             let node = self.nodes.get(&node_id).expect("Invalid node ID");
-            if !node.has_child() {
-                total += node.sum_metadata();
+            let value = if !node.has_child() {
+                Some(node.sum_metadata())
             } else {
-                // TODO: avoid visiting the same node multiple times
-                stack.append(&mut node.get_child_by_metadata());
+                node.get_child_by_metadata().into_iter().try_fold(
+                    0_u64,
+                    |total, child_id| {
+                        total.checked_add(summaries[child_id as usize].value?)
+                    },
+                )
+            };
+            let mut height = 0;
+            let mut subtree_metadata = node.sum_metadata();
+            for child_id in node.child.iter() {
+                let child = &summaries[*child_id as usize];
+                height = max(height, child.height + 1);
+                subtree_metadata += child.subtree_metadata;
             }
+
+            let summary = &mut summaries[node_id as usize];
+            summary.value = value;
+            summary.height = height;
+            summary.subtree_metadata = subtree_metadata;
         }
-        total
+        for node_id in 0..self.next_node_id {
+            let depth = summaries[node_id as usize].depth;
+            for child_id in self.nodes[&node_id].child.iter() {
+                let child = &mut summaries[*child_id as usize];
+                child.parent = Some(node_id);
+                child.depth = depth + 1;
+            }
+        }
+        self.summaries = summaries;
     }
 
-    fn add_node(&mut self, num_child: usize, num_meta: usize) -> NodeID {
+    fn add_node(
+        &mut self,
+        num_child: usize,
+        num_meta: usize,
+        header: usize,
+    ) -> NodeID {
         let node_id = self.next_node_id;
-        self.nodes.insert(node_id, Node::new(num_child, num_meta));
+        self.nodes
+            .insert(node_id, Node::new(num_child, num_meta, header));
         self.next_node_id += 1;
         node_id
     }
@@ -89,21 +394,27 @@ impl NodeGraph {
     fn missing_child(&self, node_id: NodeID) -> bool {
         self.nodes
             .get(&node_id)
-            .map_or(false, |node| node.missing_child())
+            .is_some_and(|node| node.missing_child())
     }
 
-    fn add_metadata(&mut self, node_id: NodeID, metadata: &mut Iter<u8>) {
-        self.nodes
-            .entry(node_id)
-            .and_modify(|node| node.add_metadata(metadata));
+    fn add_metadata(
+        &mut self,
+        node_id: NodeID,
+        metadata: &mut Tokens,
+    ) -> Result<(), ParseError> {
+        match self.nodes.get_mut(&node_id) {
+            Some(node) => node.add_metadata(metadata),
+            None => Ok(()),
+        }
     }
 }
 
 impl Node {
-    pub fn new(num_child: usize, num_meta: usize) -> Self {
+    pub fn new(num_child: usize, num_meta: usize, header: usize) -> Self {
         Node {
             num_child,
             num_meta,
+            header,
             child: Vec::with_capacity(num_child),
             metadata: Vec::with_capacity(num_meta),
         }
@@ -118,13 +429,19 @@ impl Node {
         self.child.len() < self.num_child
     }
 
-    pub fn add_metadata(&mut self, metadata: &mut Iter<u8>) {
-        self.metadata.extend(metadata.take(self.num_meta));
-        assert!(self.metadata.len() == self.num_meta);
+    fn add_metadata(
+        &mut self,
+        metadata: &mut Tokens,
+    ) -> Result<(), ParseError> {
+        let values = metadata
+            .take(self.num_meta)
+            .ok_or(ParseError::UnexpectedEnd(self.header))?;
+        self.metadata.extend_from_slice(values);
+        Ok(())
     }
 
-    pub fn sum_metadata(&self) -> u32 {
-        self.metadata.iter().map(|val| u32::from(*val)).sum()
+    pub fn sum_metadata(&self) -> u64 {
+        self.metadata.iter().map(|val| u64::from(*val)).sum()
     }
 
     pub fn has_child(&self) -> bool {
@@ -135,16 +452,16 @@ impl Node {
         self.metadata
             .iter()
             .filter(|&val| *val > 0)
-            .filter_map(|val| self.child.get(usize::from(val - 1)))
+            .filter_map(|val| self.child.get(*val as usize - 1))
             .cloned()
             .collect()
     }
 }
 
-pub fn part1(graph: &NodeGraph) -> u32 {
+pub fn part1(graph: &NodeGraph) -> u64 {
     graph.sum_metadata()
 }
 
-pub fn part2(graph: &NodeGraph) -> u32 {
+pub fn part2(graph: &NodeGraph) -> Result<u64, Error> {
     graph.total_value()
 }
@@ -1,6 +1,6 @@
 use clap::{crate_description, App, Arg};
 use day08::{part1, part2, NodeGraph};
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{Error, ErrorKind, Read};
 use std::process::exit;
 
@@ -12,20 +12,48 @@ fn main() {
                 .required(true)
                 .index(1),
         )
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .value_name("FILE")
+                .help("Writes the license tree as JSON"),
+        )
+        .arg(
+            Arg::with_name("dot")
+                .long("dot")
+                .value_name("FILE")
+                .help("Writes the license tree in Graphviz DOT format"),
+        )
         .get_matches();
 
     println!(crate_description!());
     let input = read_input(args.value_of("INPUT").unwrap());
-    let graph = NodeGraph::build_graph(&input);
+    let graph = match NodeGraph::build_graph(&input) {
+        Ok(graph) => graph,
+        Err(err) => {
+            println!("Invalid license data: {}", err);
+            exit(5);
+        }
+    };
     println!("Part 1: {}", part1(&graph));
-    println!("Part 2: {}", part2(&graph));
+    match part2(&graph) {
+        Ok(value) => println!("Part 2: {}", value),
+        Err(err) => println!("Part 2: {}", err),
+    }
+
+    if let Some(filename) = args.value_of("json") {
+        write_output(filename, &graph.to_json());
+    }
+    if let Some(filename) = args.value_of("dot") {
+        write_output(filename, &graph.to_dot());
+    }
 }
 
-fn read_input(filename: &str) -> Vec<u8> {
+fn read_input(filename: &str) -> Vec<u32> {
     let mut file = match File::open(filename) {
         Ok(file) => file,
         Err(err) => {
-            println!("Failed to open file '{}': {}", filename, err.to_string());
+            println!("Failed to open file '{}': {}", filename, err);
             exit(2);
         }
     };
@@ -34,17 +62,13 @@ fn read_input(filename: &str) -> Vec<u8> {
     match file.read_to_string(&mut contents) {
         Ok(_) => (),
         Err(err) => {
-            println!(
-                "Failed to read input file '{}': {}",
-                filename,
-                err.to_string()
-            );
+            println!("Failed to read input file '{}': {}", filename, err);
             exit(3);
         }
     };
 
     match contents
-        .split(' ')
+        .split_whitespace()
         .map(|number| {
             number
                 .parse()
@@ -54,12 +78,15 @@ fn read_input(filename: &str) -> Vec<u8> {
     {
         Ok(numbers) => numbers,
         Err(err) => {
-            println!(
-                "Failed to parse input file '{}': {}",
-                filename,
-                err.to_string()
-            );
+            println!("Failed to parse input file '{}': {}", filename, err);
             exit(4);
         }
     }
 }
+
+fn write_output(filename: &str, contents: &str) {
+    if let Err(err) = fs::write(filename, contents) {
+        println!("Failed to write output file '{}': {}", filename, err);
+        exit(6);
+    }
+}
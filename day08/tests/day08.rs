@@ -1,14 +1,140 @@
-use day08::{part1, part2, NodeGraph};
+use day08::{part1, part2, NodeGraph, ParseError};
 
-fn parse_input(input: &str) -> Vec<u8> {
+fn parse_input(input: &str) -> Vec<u32> {
     input.split(' ').map(|s| s.parse().unwrap()).collect()
 }
 
+const EXAMPLE: &str = "2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2";
+
 #[test]
 fn examples() {
-    let input = "2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2";
-    let values = parse_input(&input);
-    let graph = NodeGraph::build_graph(&values);
+    let values = parse_input(EXAMPLE);
+    let graph = NodeGraph::build_graph(&values).unwrap();
     assert_eq!(part1(&graph), 138);
-    assert_eq!(part2(&graph), 66);
+    assert_eq!(part2(&graph).unwrap(), 66);
+}
+
+#[test]
+fn invalid_input() {
+    let build = |input: &str| NodeGraph::build_graph(&parse_input(input)).err();
+    assert_eq!(
+        NodeGraph::build_graph(&[]).err(),
+        Some(ParseError::UnexpectedEnd(0))
+    );
+    assert_eq!(build("1"), Some(ParseError::UnexpectedEnd(0)));
+    assert_eq!(build("0 3 10 11"), Some(ParseError::UnexpectedEnd(0)));
+    assert_eq!(build("1 1 0 1 99"), Some(ParseError::UnexpectedEnd(0)));
+    assert_eq!(build("1 1 0 1"), Some(ParseError::UnexpectedEnd(0)));
+    assert_eq!(build("1 1 0 3 1 2"), Some(ParseError::UnexpectedEnd(2)));
+    assert_eq!(build("2 1 0 1 7 1 2 3"), Some(ParseError::UnexpectedEnd(5)));
+    assert_eq!(build("0 1 99 0 1"), Some(ParseError::TrailingData(3)));
+    assert_eq!(
+        build("4294967295 4294967295 0 0"),
+        Some(ParseError::UnexpectedEnd(0))
+    );
+}
+
+#[test]
+fn large_counts() {
+    let mut values = vec![300, 1];
+    for _ in 0..300 {
+        values.extend_from_slice(&[0, 1, 1000]);
+    }
+    values.push(300);
+    let graph = NodeGraph::build_graph(&values).unwrap();
+    assert_eq!(part1(&graph), 300 * 1000 + 300);
+    assert_eq!(part2(&graph).unwrap(), 1000);
+}
+
+#[test]
+fn node_values() {
+    let graph = NodeGraph::build_graph(&parse_input(EXAMPLE)).unwrap();
+    assert_eq!(graph.node_value(0), Some(66));
+    assert_eq!(graph.node_value(1), Some(33));
+    assert_eq!(graph.node_value(2), Some(0));
+    assert_eq!(graph.node_value(3), Some(99));
+    assert_eq!(graph.node_value(4), None);
+}
+
+#[test]
+fn shared_subtrees() {
+    // Every node refers to its only child 50 times, which would take 50^60
+    // visits without memoization; values past 50^11 do not fit in a u64
+    let depth = 60;
+    let mut values = Vec::new();
+    for _ in 0..depth {
+        values.extend_from_slice(&[1, 50]);
+    }
+    values.extend_from_slice(&[0, 1, 1]);
+    for _ in 0..depth {
+        values.extend_from_slice(&[1; 50]);
+    }
+    let graph = NodeGraph::build_graph(&values).unwrap();
+    assert_eq!(graph.node_value(depth - 1), Some(50));
+    assert_eq!(graph.node_value(depth - 10), Some(50_u64.pow(10)));
+    assert_eq!(graph.node_value(depth - 11), Some(50_u64.pow(11)));
+    assert_eq!(graph.node_value(depth - 12), None);
+    assert_eq!(graph.node_value(0), None);
+    assert_eq!(
+        part2(&graph).unwrap_err().kind(),
+        std::io::ErrorKind::InvalidData
+    );
+    assert!(graph.to_json().contains("\"value\":null"));
+}
+
+#[test]
+fn tree_queries() {
+    let graph = NodeGraph::build_graph(&parse_input(EXAMPLE)).unwrap();
+    assert_eq!(graph.num_nodes(), 4);
+    assert_eq!(graph.root(), Some(0));
+    assert_eq!(graph.children(0), Some(&[1, 2][..]));
+    assert_eq!(graph.metadata(2), Some(&[2][..]));
+    assert_eq!(graph.pre_order().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    assert_eq!(graph.post_order().collect::<Vec<_>>(), vec![1, 3, 2, 0]);
+
+    let parents: Vec<_> = (0..5).map(|id| graph.parent(id)).collect();
+    assert_eq!(parents, vec![None, Some(0), Some(0), Some(2), None]);
+    let depths: Vec<_> = (0..4).map(|id| graph.depth(id).unwrap()).collect();
+    assert_eq!(depths, vec![0, 1, 1, 2]);
+    let heights: Vec<_> = (0..4).map(|id| graph.height(id).unwrap()).collect();
+    assert_eq!(heights, vec![2, 0, 1, 0]);
+    let sums: Vec<_> = (0..4)
+        .map(|id| graph.subtree_metadata(id).unwrap())
+        .collect();
+    assert_eq!(sums, vec![138, 33, 101, 99]);
+}
+
+#[test]
+fn tree_export() {
+    let values = parse_input(EXAMPLE);
+    let graph = NodeGraph::build_graph(&values).unwrap();
+    assert_eq!(graph.to_numbers(), values);
+    assert_eq!(
+        graph.to_json(),
+        "{\"id\":0,\"metadata\":[1,1,2],\"value\":66,\"children\":[\
+         {\"id\":1,\"metadata\":[10,11,12],\"value\":33,\"children\":[]},\
+         {\"id\":2,\"metadata\":[2],\"value\":0,\"children\":[\
+         {\"id\":3,\"metadata\":[99],\"value\":99,\"children\":[]}]}]}"
+    );
+    assert_eq!(
+        graph.to_dot(),
+        "digraph tree {
+    0 [label=\"0\\nmetadata 1 1 2\\nvalue 66\"];
+    1 [label=\"1\\nmetadata 10 11 12\\nvalue 33\"];
+    2 [label=\"2\\nmetadata 2\\nvalue 0\"];
+    3 [label=\"3\\nmetadata 99\\nvalue 99\"];
+    0 -> 1;
+    0 -> 2;
+    2 -> 3;
+}
+"
+    );
+
+    let mut values = vec![300, 1];
+    for _ in 0..300 {
+        values.extend_from_slice(&[1, 0, 0, 2, 7, 8]);
+    }
+    values.push(300);
+    let graph = NodeGraph::build_graph(&values).unwrap();
+    assert_eq!(graph.to_numbers(), values);
 }
@@ -1,40 +1,79 @@
-use std::collections::HashMap;
+type MarbleNum = u32;
 
-const MAGIC_PLAY: u32 = 23;
+// Every marble that is a multiple of scoring_multiple removes the marble
+// removal_offset positions counter-clockwise from the current one; other
+// marbles go between the marbles insertion_offset and insertion_offset + 1
+// positions clockwise. A scoring marble is either kept by the player or
+// placed as usual before the removal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rules {
+    pub scoring_multiple: u32,
+    pub removal_offset: u32,
+    pub insertion_offset: u32,
+    pub keep_scoring_marble: bool,
+}
 
-type MarbleNum = u32;
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            scoring_multiple: 23,
+            removal_offset: 7,
+            insertion_offset: 1,
+            keep_scoring_marble: true,
+        }
+    }
+}
 
+#[derive(Clone, Copy)]
 struct Marble {
     pub prev: MarbleNum,
     pub next: MarbleNum,
 }
 
+// Circle of marbles as a doubly linked list indexed by marble number;
+// removed marbles keep their entries but are no longer linked
 pub struct MarbleGame {
     curr_marble: MarbleNum,
     next_marble: MarbleNum,
     curr_player: usize,
-    marbles: HashMap<MarbleNum, Marble>,
-    scores: Vec<u32>,
+    marbles: Vec<Marble>,
+    num_placed: u32,
+    scores: Vec<u64>,
+    rules: Rules,
 }
 
 impl MarbleGame {
-    pub fn new(num_players: usize) -> Self {
+    pub fn new(num_players: usize, rules: Rules) -> Self {
         MarbleGame {
             curr_marble: 0,
             next_marble: 1,
             curr_player: 0,
-            marbles: vec![(0, Marble { prev: 0, next: 0 })]
-                .into_iter()
-                .collect(),
+            marbles: vec![Marble { prev: 0, next: 0 }],
+            num_placed: 1,
             scores: vec![0; num_players],
+            rules,
         }
     }
 
     pub fn play_until(&mut self, last_marble: MarbleNum) {
-        while self.curr_marble < last_marble {
-            if self.next_marble % MAGIC_PLAY == 0 {
-                let points = self.delete_marble();
-                self.scores[self.curr_player] += points + self.next_marble;
+        self.marbles.reserve(
+            (last_marble as usize + 1).saturating_sub(self.marbles.len()),
+        );
+        while self.next_marble <= last_marble {
+            if self.next_marble.is_multiple_of(self.rules.scoring_multiple) {
+                let mut points = 0;
+                if self.rules.keep_scoring_marble {
+                    points += u64::from(self.next_marble);
+                    // Keep marble numbers aligned with their index
+                    self.marbles.push(Marble { prev: 0, next: 0 });
+                } else {
+                    self.insert_marble();
+                }
+                // The circle always keeps at least one marble
+                if self.num_placed > 1 {
+                    points += u64::from(self.delete_marble());
+                }
+                self.scores[self.curr_player] += points;
             } else {
                 self.insert_marble();
             }
@@ -43,70 +82,58 @@ impl MarbleGame {
         }
     }
 
-    pub fn highest_score(&self) -> u32 {
+    pub fn highest_score(&self) -> u64 {
         *self.scores.iter().max().unwrap_or(&0)
     }
 
+    fn marble(&self, marble_num: MarbleNum) -> Marble {
+        self.marbles[marble_num as usize]
+    }
+
+    fn marble_mut(&mut self, marble_num: MarbleNum) -> &mut Marble {
+        &mut self.marbles[marble_num as usize]
+    }
+
     fn insert_marble(&mut self) {
-        let after_marble = self
-            .marbles
-            .get(&self.curr_marble)
-            .map(|marble| marble.next)
-            .unwrap();
-        let before_marble = self
-            .marbles
-            .get(&after_marble)
-            .map(|marble| marble.next)
-            .unwrap();
+        let mut after_marble = self.curr_marble;
+        for _ in 0..self.rules.insertion_offset % self.num_placed {
+            after_marble = self.marble(after_marble).next;
+        }
+        let before_marble = self.marble(after_marble).next;
         let new_marble = self.next_marble;
-        self.marbles.insert(
-            self.next_marble,
-            Marble {
-                prev: after_marble,
-                next: before_marble,
-            },
-        );
-        self.marbles
-            .entry(after_marble)
-            .and_modify(|marble| marble.next = new_marble);
-        self.marbles
-            .entry(before_marble)
-            .and_modify(|marble| marble.prev = new_marble);
+        self.marbles.push(Marble {
+            prev: after_marble,
+            next: before_marble,
+        });
+        self.marble_mut(after_marble).next = new_marble;
+        self.marble_mut(before_marble).prev = new_marble;
         self.curr_marble = new_marble;
+        self.num_placed += 1;
     }
 
     fn delete_marble(&mut self) -> u32 {
         let mut delete_marble = self.curr_marble;
-        for _ in 1..=7 {
-            delete_marble = self
-                .marbles
-                .get(&delete_marble)
-                .map(|marble| marble.prev)
-                .unwrap();
+        for _ in 0..self.rules.removal_offset % self.num_placed {
+            delete_marble = self.marble(delete_marble).prev;
         }
-        let (before_marble, after_marble) = self
-            .marbles
-            .get(&delete_marble)
-            .map(|marble| (marble.prev, marble.next))
-            .unwrap();
-        self.marbles
-            .entry(before_marble)
-            .and_modify(|marble| marble.next = after_marble);
-        self.marbles
-            .entry(after_marble)
-            .and_modify(|marble| marble.prev = before_marble);
-        self.marbles.remove(&delete_marble);
+        let Marble {
+            prev: before_marble,
+            next: after_marble,
+        } = self.marble(delete_marble);
+        self.marble_mut(before_marble).next = after_marble;
+        self.marble_mut(after_marble).prev = before_marble;
         self.curr_marble = after_marble;
+        self.num_placed -= 1;
         delete_marble
     }
 }
 
-pub fn part1(game: &mut MarbleGame, last_marble: MarbleNum) -> u32 {
+pub fn part1(game: &mut MarbleGame, last_marble: MarbleNum) -> u64 {
     game.play_until(last_marble);
     game.highest_score()
 }
 
-pub fn part2(game: &mut MarbleGame, last_marble: MarbleNum) -> u32 {
+pub fn part2(game: &mut MarbleGame, last_marble: MarbleNum) -> u64 {
     game.play_until(100 * last_marble);
     game.highest_score()
 }
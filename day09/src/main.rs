@@ -1,5 +1,5 @@
 use clap::{crate_description, value_t_or_exit, App, Arg};
-use day09::{part1, part2, MarbleGame};
+use day09::{part1, part2, MarbleGame, Rules};
 
 fn main() {
     let args = App::new(crate_description!())
@@ -15,12 +15,49 @@ fn main() {
                 .required(true)
                 .index(2),
         )
+        .arg(
+            Arg::with_name("scoring_multiple")
+                .long("scoring-multiple")
+                .value_name("NUM")
+                .help("Marbles that are multiples of this number score"),
+        )
+        .arg(
+            Arg::with_name("removal_offset")
+                .long("removal-offset")
+                .value_name("NUM")
+                .help("Counter-clockwise position of the marble removed"),
+        )
+        .arg(
+            Arg::with_name("insertion_offset")
+                .long("insertion-offset")
+                .value_name("NUM")
+                .help("Clockwise position after which marbles are placed"),
+        )
+        .arg(
+            Arg::with_name("place_scoring_marble")
+                .long("place-scoring-marble")
+                .help("Places scoring marbles instead of keeping them"),
+        )
         .get_matches();
 
     println!(crate_description!());
     let num_players = value_t_or_exit!(args.value_of("NUM_PLAYERS"), usize);
     let last_marble = value_t_or_exit!(args.value_of("LAST_MARBLE"), u32);
-    let mut game = MarbleGame::new(num_players);
+    let mut rules = Rules::default();
+    if args.is_present("scoring_multiple") {
+        rules.scoring_multiple =
+            value_t_or_exit!(args.value_of("scoring_multiple"), u32);
+    }
+    if args.is_present("removal_offset") {
+        rules.removal_offset =
+            value_t_or_exit!(args.value_of("removal_offset"), u32);
+    }
+    if args.is_present("insertion_offset") {
+        rules.insertion_offset =
+            value_t_or_exit!(args.value_of("insertion_offset"), u32);
+    }
+    rules.keep_scoring_marble = !args.is_present("place_scoring_marble");
+    let mut game = MarbleGame::new(num_players, rules);
     println!("Part 1: {}", part1(&mut game, last_marble));
     println!("Part 2: {}", part2(&mut game, last_marble));
 }
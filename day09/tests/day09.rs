@@ -1,11 +1,73 @@
-use day09::{part1, MarbleGame};
+use day09::{part1, part2, MarbleGame, Rules};
 
 #[test]
 fn examples_part1() {
-    assert_eq!(part1(&mut MarbleGame::new(9), 25), 32);
-    assert_eq!(part1(&mut MarbleGame::new(10), 1618), 8317);
-    assert_eq!(part1(&mut MarbleGame::new(13), 7999), 146373);
-    assert_eq!(part1(&mut MarbleGame::new(17), 1104), 2764);
-    assert_eq!(part1(&mut MarbleGame::new(21), 6111), 54718);
-    assert_eq!(part1(&mut MarbleGame::new(30), 5807), 37305);
+    assert_eq!(part1(&mut MarbleGame::new(9, Rules::default()), 25), 32);
+    assert_eq!(
+        part1(&mut MarbleGame::new(10, Rules::default()), 1618),
+        8317
+    );
+    assert_eq!(
+        part1(&mut MarbleGame::new(13, Rules::default()), 7999),
+        146373
+    );
+    assert_eq!(
+        part1(&mut MarbleGame::new(17, Rules::default()), 1104),
+        2764
+    );
+    assert_eq!(
+        part1(&mut MarbleGame::new(21, Rules::default()), 6111),
+        54718
+    );
+    assert_eq!(
+        part1(&mut MarbleGame::new(30, Rules::default()), 5807),
+        37305
+    );
+}
+
+#[test]
+fn examples_part2() {
+    let mut game = MarbleGame::new(9, Rules::default());
+    assert_eq!(part1(&mut game, 25), 32);
+    assert_eq!(part2(&mut game, 25), 22563);
+    let mut game = MarbleGame::new(10, Rules::default());
+    assert_eq!(part1(&mut game, 1618), 8317);
+    assert_eq!(part2(&mut game, 1618), 74765078);
+}
+
+#[test]
+fn large_scores() {
+    assert_eq!(
+        part1(&mut MarbleGame::new(1, Rules::default()), 3_000_000),
+        256651682833
+    );
+}
+
+#[test]
+fn other_rules() {
+    // Insert right after the current marble and remove the marble just
+    // before it every third marble
+    let rules = Rules {
+        scoring_multiple: 3,
+        removal_offset: 1,
+        insertion_offset: 0,
+        keep_scoring_marble: true,
+    };
+    let mut game = MarbleGame::new(2, rules);
+    assert_eq!(part1(&mut game, 6), 10);
+
+    // Place the scoring marble, then remove it again right away
+    let rules = Rules {
+        removal_offset: 0,
+        keep_scoring_marble: false,
+        ..Rules::default()
+    };
+    assert_eq!(part1(&mut MarbleGame::new(9, rules), 25), 23);
+
+    // No marble ever scores
+    let rules = Rules {
+        scoring_multiple: 0,
+        ..Rules::default()
+    };
+    assert_eq!(part1(&mut MarbleGame::new(9, rules), 100), 0);
 }